@@ -0,0 +1,28 @@
+#[cfg(feature = "mock-oracle")]
+use amm::MockFeed;
+use amm::{Config, Order};
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::error::*;
+
+pub fn decode_config(data: &[u8]) -> Result<Config> {
+    Ok(Config::try_deserialize(&mut &data[..])?)
+}
+
+pub fn decode_order(data: &[u8]) -> Result<Order> {
+    Ok(Order::try_deserialize(&mut &data[..])?)
+}
+
+#[cfg(feature = "mock-oracle")]
+pub fn decode_mock_feed(data: &[u8]) -> Result<MockFeed> {
+    Ok(MockFeed::try_deserialize(&mut &data[..])?)
+}
+
+pub fn decode_token_account(data: &[u8]) -> Result<TokenAccount> {
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?)
+}
+
+pub fn decode_mint(data: &[u8]) -> Result<Mint> {
+    Ok(Mint::try_deserialize(&mut &data[..])?)
+}
@@ -0,0 +1,33 @@
+use constant_product_curve::CurveError;
+use solana_client::client_error::ClientError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AmmClientError {
+    #[error("rpc error: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("account error: {0}")]
+    Account(#[from] anchor_lang::error::Error),
+    #[error("curve error: {0:?}")]
+    Curve(CurveError),
+    #[error("pool has no liquidity, initial deposit amounts must be chosen by the caller")]
+    EmptyPool,
+    #[error("slippage must be at most 10000 basis points")]
+    InvalidSlippage,
+    #[error("amount overflow")]
+    Overflow,
+}
+
+impl From<ClientError> for AmmClientError {
+    fn from(error: ClientError) -> AmmClientError {
+        AmmClientError::Rpc(Box::new(error))
+    }
+}
+
+impl From<CurveError> for AmmClientError {
+    fn from(error: CurveError) -> AmmClientError {
+        AmmClientError::Curve(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, AmmClientError>;
@@ -0,0 +1,447 @@
+use anchor_lang::{
+    prelude::{AccountMeta, Pubkey},
+    solana_program::instruction::Instruction,
+    system_program, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, token};
+
+use crate::pda::*;
+
+fn instruction(accounts: Vec<AccountMeta>, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: amm::ID,
+        accounts,
+        data,
+    }
+}
+
+pub fn initialize(
+    initializer: &Pubkey,
+    pool: &PoolAddresses,
+    fee: u16,
+    authority: Option<Pubkey>,
+    creator_fee: u16,
+) -> Instruction {
+    let accounts = amm::accounts::Initialize {
+        initializer: *initializer,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        mint_lp: pool.mint_lp,
+        vault_x: pool.vault_x,
+        vault_y: pool.vault_y,
+        config: pool.config,
+        creator_fee_x: creator_fee_vault_address(&pool.config, &pool.mint_x).0,
+        creator_fee_y: creator_fee_vault_address(&pool.config, &pool.mint_y).0,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::Initialize {
+        seed: pool.seed,
+        fee,
+        authority,
+        creator_fee,
+    };
+    instruction(accounts.to_account_metas(None), data.data())
+}
+
+pub fn deposit(
+    lp_provider: &Pubkey,
+    pool: &PoolAddresses,
+    amount: u64,
+    max_x: u64,
+    max_y: u64,
+) -> Instruction {
+    let accounts = amm::accounts::Deposit {
+        lp_provider: *lp_provider,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        mint_lp: pool.mint_lp,
+        vault_x: pool.vault_x,
+        vault_y: pool.vault_y,
+        lp_provider_mint_x: ata(lp_provider, &pool.mint_x),
+        lp_provider_mint_y: ata(lp_provider, &pool.mint_y),
+        lp_provider_mint_lp: ata(lp_provider, &pool.mint_lp),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::Deposit {
+        amount,
+        max_x,
+        max_y,
+    };
+    instruction(accounts.to_account_metas(None), data.data())
+}
+
+pub fn withdraw(
+    lp_provider: &Pubkey,
+    pool: &PoolAddresses,
+    amount: u64,
+    min_x: u64,
+    min_y: u64,
+) -> Instruction {
+    let accounts = amm::accounts::Withdraw {
+        lp_provider: *lp_provider,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        mint_lp: pool.mint_lp,
+        vault_x: pool.vault_x,
+        vault_y: pool.vault_y,
+        lp_provider_mint_x: ata(lp_provider, &pool.mint_x),
+        lp_provider_mint_y: ata(lp_provider, &pool.mint_y),
+        lp_provider_mint_lp: ata(lp_provider, &pool.mint_lp),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    // the program names the withdraw bounds max_x/max_y but treats them as minimums
+    let data = amm::instruction::Withdraw {
+        amount,
+        max_x: min_x,
+        max_y: min_y,
+    };
+    instruction(accounts.to_account_metas(None), data.data())
+}
+
+pub fn migrate_config(authority: &Pubkey, config: &Pubkey) -> Instruction {
+    let accounts = amm::accounts::MigrateConfig {
+        authority: *authority,
+        config: *config,
+        system_program: system_program::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::MigrateConfig {}.data(),
+    )
+}
+
+pub fn update_oracle(
+    authority: &Pubkey,
+    config: &Pubkey,
+    oracle: Pubkey,
+    max_deviation_bps: u16,
+    max_oracle_age: u32,
+) -> Instruction {
+    let accounts = amm::accounts::UpdateOracle {
+        authority: *authority,
+        config: *config,
+    };
+    let data = amm::instruction::UpdateOracle {
+        oracle,
+        max_deviation_bps,
+        max_oracle_age,
+    };
+    instruction(accounts.to_account_metas(None), data.data())
+}
+
+#[cfg(feature = "mock-oracle")]
+pub fn set_mock_feed(authority: &Pubkey, price: u64) -> Instruction {
+    let accounts = amm::accounts::SetMockFeed {
+        authority: *authority,
+        feed: mock_feed_address(authority).0,
+        system_program: system_program::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::SetMockFeed { price }.data(),
+    )
+}
+
+// `orders` are resting orders the program should try to fill after the swap
+pub fn swap(
+    user: &Pubkey,
+    pool: &PoolAddresses,
+    oracle: Option<Pubkey>,
+    is_x: bool,
+    amount: u64,
+    min: u64,
+    orders: &[OrderAddresses],
+) -> Instruction {
+    let accounts = amm::accounts::Swap {
+        user: *user,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        mint_lp: pool.mint_lp,
+        vault_x: pool.vault_x,
+        vault_y: pool.vault_y,
+        user_mint_x: ata(user, &pool.mint_x),
+        user_mint_y: ata(user, &pool.mint_y),
+        creator_fee_x: pool.creator_fee_vaults.map(|(x, _)| x),
+        creator_fee_y: pool.creator_fee_vaults.map(|(_, y)| y),
+        oracle,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    for order in orders {
+        metas.push(AccountMeta::new(order.order, false));
+        metas.push(AccountMeta::new(order.vault_x, false));
+        metas.push(AccountMeta::new(order.vault_y, false));
+    }
+    let data = amm::instruction::Swap { is_x, amount, min };
+    instruction(metas, data.data())
+}
+
+pub fn place_order(
+    owner: &Pubkey,
+    pool: &PoolAddresses,
+    seed: u64,
+    is_x: bool,
+    amount: u64,
+    price: u64,
+) -> Instruction {
+    let order = pool.order(owner, seed);
+    let accounts = amm::accounts::PlaceOrder {
+        owner: *owner,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        order: order.order,
+        order_vault_x: order.vault_x,
+        order_vault_y: order.vault_y,
+        owner_mint_x: ata(owner, &pool.mint_x),
+        owner_mint_y: ata(owner, &pool.mint_y),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    let data = amm::instruction::PlaceOrder {
+        seed,
+        is_x,
+        amount,
+        price,
+    };
+    instruction(accounts.to_account_metas(None), data.data())
+}
+
+pub fn crank_fill(
+    cranker: &Pubkey,
+    pool: &PoolAddresses,
+    order: &OrderAddresses,
+    oracle: Option<Pubkey>,
+) -> Instruction {
+    let accounts = amm::accounts::CrankFill {
+        cranker: *cranker,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        mint_lp: pool.mint_lp,
+        vault_x: pool.vault_x,
+        vault_y: pool.vault_y,
+        order: order.order,
+        order_vault_x: order.vault_x,
+        order_vault_y: order.vault_y,
+        creator_fee_x: pool.creator_fee_vaults.map(|(x, _)| x),
+        creator_fee_y: pool.creator_fee_vaults.map(|(_, y)| y),
+        oracle,
+        token_program: token::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::CrankFill {}.data(),
+    )
+}
+
+pub fn claim_order(owner: &Pubkey, pool: &PoolAddresses, seed: u64) -> Instruction {
+    let order = pool.order(owner, seed);
+    let accounts = amm::accounts::ClaimOrder {
+        owner: *owner,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        order: order.order,
+        order_vault_x: order.vault_x,
+        order_vault_y: order.vault_y,
+        owner_mint_x: ata(owner, &pool.mint_x),
+        owner_mint_y: ata(owner, &pool.mint_y),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::ClaimOrder {}.data(),
+    )
+}
+
+pub fn cancel_order(owner: &Pubkey, pool: &PoolAddresses, seed: u64) -> Instruction {
+    let order = pool.order(owner, seed);
+    let accounts = amm::accounts::CancelOrder {
+        owner: *owner,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        order: order.order,
+        order_vault_x: order.vault_x,
+        order_vault_y: order.vault_y,
+        owner_mint_x: ata(owner, &pool.mint_x),
+        owner_mint_y: ata(owner, &pool.mint_y),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::CancelOrder {}.data(),
+    )
+}
+
+pub fn claim_creator_fees(creator: &Pubkey, pool: &PoolAddresses) -> Instruction {
+    let accounts = amm::accounts::ClaimCreatorFees {
+        creator: *creator,
+        mint_x: pool.mint_x,
+        mint_y: pool.mint_y,
+        config: pool.config,
+        creator_fee_x: creator_fee_vault_address(&pool.config, &pool.mint_x).0,
+        creator_fee_y: creator_fee_vault_address(&pool.config, &pool.mint_y).0,
+        creator_mint_x: ata(creator, &pool.mint_x),
+        creator_mint_y: ata(creator, &pool.mint_y),
+        system_program: system_program::ID,
+        token_program: token::ID,
+        associated_token_program: associated_token::ID,
+    };
+    instruction(
+        accounts.to_account_metas(None),
+        amm::instruction::ClaimCreatorFees {}.data(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use anchor_lang::idl::types::IdlInstructionAccountItem;
+
+    use super::*;
+
+    // Checks `ix` lists the accounts the program's IDL expects, in the same order and with
+    // the same writable/signer flags, and returns the key passed for each named account
+    fn assert_matches_idl(
+        ix: &Instruction,
+        idl: Vec<IdlInstructionAccountItem>,
+    ) -> BTreeMap<String, Pubkey> {
+        assert!(ix.accounts.len() >= idl.len());
+
+        let mut keys = BTreeMap::new();
+        for (meta, item) in ix.accounts.iter().zip(idl) {
+            let IdlInstructionAccountItem::Single(account) = item else {
+                panic!("unexpected composite account");
+            };
+            // Anchor passes the program id, read-only, in place of a missing optional account
+            if !(account.optional && meta.pubkey == amm::ID) {
+                assert_eq!(meta.is_writable, account.writable, "{}", account.name);
+                assert_eq!(meta.is_signer, account.signer, "{}", account.name);
+            }
+            keys.insert(account.name, meta.pubkey);
+        }
+        keys
+    }
+
+    fn pool() -> PoolAddresses {
+        PoolAddresses::new(7, Pubkey::new_unique(), Pubkey::new_unique())
+    }
+
+    macro_rules! idl_accounts {
+        ($accounts:ident) => {
+            amm::$accounts::__anchor_private_gen_idl_accounts(
+                &mut BTreeMap::new(),
+                &mut BTreeMap::new(),
+            )
+        };
+    }
+
+    #[test]
+    fn liquidity_instructions_match_the_idl() {
+        let (user, pool) = (Pubkey::new_unique(), pool());
+
+        let keys = assert_matches_idl(
+            &initialize(&user, &pool, 30, None, 0),
+            idl_accounts!(Initialize),
+        );
+        assert_eq!(keys["config"], pool.config);
+        assert_eq!(keys["mint_lp"], pool.mint_lp);
+        assert_eq!(keys["vault_x"], pool.vault_x);
+
+        let keys = assert_matches_idl(&deposit(&user, &pool, 1, 1, 1), idl_accounts!(Deposit));
+        assert_eq!(keys["lp_provider_mint_lp"], ata(&user, &pool.mint_lp));
+
+        let keys = assert_matches_idl(&withdraw(&user, &pool, 1, 1, 1), idl_accounts!(Withdraw));
+        assert_eq!(keys["lp_provider_mint_x"], ata(&user, &pool.mint_x));
+
+        assert_matches_idl(
+            &claim_creator_fees(&user, &pool),
+            idl_accounts!(ClaimCreatorFees),
+        );
+    }
+
+    #[test]
+    fn config_instructions_match_the_idl() {
+        let (user, pool) = (Pubkey::new_unique(), pool());
+
+        assert_matches_idl(
+            &migrate_config(&user, &pool.config),
+            idl_accounts!(MigrateConfig),
+        );
+        assert_matches_idl(
+            &update_oracle(&user, &pool.config, Pubkey::new_unique(), 100, 60),
+            idl_accounts!(UpdateOracle),
+        );
+    }
+
+    #[test]
+    fn swaps_append_resting_orders_after_the_idl_accounts() {
+        let (user, oracle) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool = pool().with_creator_fee_vaults();
+        let order = pool.order(&Pubkey::new_unique(), 3);
+        let idl = idl_accounts!(Swap);
+        let named = idl.len();
+
+        let ix = swap(&user, &pool, Some(oracle), true, 1, 1, &[order]);
+        let keys = assert_matches_idl(&ix, idl);
+
+        assert_eq!(keys["oracle"], oracle);
+        assert_eq!(keys["creator_fee_x"], pool.creator_fee_vaults.unwrap().0);
+        assert_eq!(
+            ix.accounts[named..]
+                .iter()
+                .map(|meta| (meta.pubkey, meta.is_writable))
+                .collect::<Vec<_>>(),
+            [
+                (order.order, true),
+                (order.vault_x, true),
+                (order.vault_y, true)
+            ]
+        );
+
+        // Pools without an oracle or creator fees leave those accounts out
+        let ix = swap(&user, &self::pool(), None, true, 1, 1, &[]);
+        let keys = assert_matches_idl(&ix, idl_accounts!(Swap));
+        assert_eq!(keys["oracle"], amm::ID);
+        assert_eq!(keys["creator_fee_y"], amm::ID);
+    }
+
+    #[test]
+    fn order_instructions_match_the_idl() {
+        let (owner, pool) = (Pubkey::new_unique(), pool());
+        let order = pool.order(&owner, 3);
+
+        let keys = assert_matches_idl(
+            &place_order(&owner, &pool, 3, true, 1, 1),
+            idl_accounts!(PlaceOrder),
+        );
+        assert_eq!(keys["order"], order.order);
+        assert_eq!(keys["order_vault_y"], order.vault_y);
+
+        assert_matches_idl(
+            &crank_fill(&Pubkey::new_unique(), &pool, &order, None),
+            idl_accounts!(CrankFill),
+        );
+        assert_matches_idl(&claim_order(&owner, &pool, 3), idl_accounts!(ClaimOrder));
+        assert_matches_idl(&cancel_order(&owner, &pool, 3), idl_accounts!(CancelOrder));
+    }
+}
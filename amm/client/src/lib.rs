@@ -0,0 +1,13 @@
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod pool;
+
+pub use accounts::*;
+pub use error::*;
+pub use instructions::*;
+pub use pda::*;
+pub use pool::*;
+
+pub use amm::ID as AMM_PROGRAM_ID;
@@ -0,0 +1,192 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+
+// Seeds mirror the `seeds = [...]` constraints in the amm program
+
+pub fn config_address(seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config", seed.to_le_bytes().as_ref()], &amm::ID)
+}
+
+pub fn lp_mint_address(config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"lp", config.as_ref()], &amm::ID)
+}
+
+pub fn order_address(config: &Pubkey, owner: &Pubkey, seed: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"order",
+            config.as_ref(),
+            owner.as_ref(),
+            seed.to_le_bytes().as_ref(),
+        ],
+        &amm::ID,
+    )
+}
+
+pub fn creator_fee_vault_address(config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"creator_fee", config.as_ref(), mint.as_ref()],
+        &amm::ID,
+    )
+}
+
+#[cfg(feature = "mock-oracle")]
+pub fn mock_feed_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"mock_feed", authority.as_ref()], &amm::ID)
+}
+
+// Pool vaults and user token accounts are plain associated token accounts
+pub fn ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, mint)
+}
+
+// Every address that makes up a pool, derived from its seed and mints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolAddresses {
+    pub seed: u64,
+    pub config: Pubkey,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub mint_lp: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+    // Creator fee vaults for x and y, only passed to swaps when the pool has a creator fee
+    pub creator_fee_vaults: Option<(Pubkey, Pubkey)>,
+}
+
+impl PoolAddresses {
+    pub fn new(seed: u64, mint_x: Pubkey, mint_y: Pubkey) -> PoolAddresses {
+        let (config, _) = config_address(seed);
+        let (mint_lp, _) = lp_mint_address(&config);
+        PoolAddresses {
+            seed,
+            config,
+            mint_x,
+            mint_y,
+            mint_lp,
+            vault_x: ata(&config, &mint_x),
+            vault_y: ata(&config, &mint_y),
+            creator_fee_vaults: None,
+        }
+    }
+
+    pub fn with_creator_fee_vaults(mut self) -> PoolAddresses {
+        self.creator_fee_vaults = Some((
+            creator_fee_vault_address(&self.config, &self.mint_x).0,
+            creator_fee_vault_address(&self.config, &self.mint_y).0,
+        ));
+        self
+    }
+
+    pub fn order(&self, owner: &Pubkey, seed: u64) -> OrderAddresses {
+        let (order, _) = order_address(&self.config, owner, seed);
+        OrderAddresses {
+            order,
+            vault_x: ata(&order, &self.mint_x),
+            vault_y: ata(&order, &self.mint_y),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderAddresses {
+    pub order: Pubkey,
+    pub vault_x: Pubkey,
+    pub vault_y: Pubkey,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use anchor_lang::idl::types::{IdlInstructionAccountItem, IdlSeed};
+
+    use super::*;
+
+    // The address the program's IDL derives for the `name` account, with account seeds
+    // looked up in `keys` and argument seeds in `args`
+    fn idl_address(
+        idl: &[IdlInstructionAccountItem],
+        name: &str,
+        keys: &BTreeMap<&str, Pubkey>,
+        args: &BTreeMap<&str, Vec<u8>>,
+    ) -> Pubkey {
+        let seed = |seed: &IdlSeed| -> Vec<u8> {
+            match seed {
+                IdlSeed::Const(seed) => seed.value.clone(),
+                IdlSeed::Arg(seed) => args[seed.path.as_str()].clone(),
+                IdlSeed::Account(seed) => keys[seed.path.as_str()].to_bytes().to_vec(),
+            }
+        };
+
+        let pda = idl
+            .iter()
+            .find_map(|item| match item {
+                IdlInstructionAccountItem::Single(account) if account.name == name => {
+                    account.pda.clone()
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| panic!("{name} has no PDA in the IDL"));
+
+        let seeds: Vec<Vec<u8>> = pda.seeds.iter().map(seed).collect();
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        let program = match &pda.program {
+            Some(program) => Pubkey::try_from(seed(program)).unwrap(),
+            None => amm::ID,
+        };
+        Pubkey::find_program_address(&seeds, &program).0
+    }
+
+    macro_rules! idl_accounts {
+        ($accounts:ident) => {
+            amm::$accounts::__anchor_private_gen_idl_accounts(
+                &mut BTreeMap::new(),
+                &mut BTreeMap::new(),
+            )
+        };
+    }
+
+    #[test]
+    fn pool_addresses_match_the_program_idl() {
+        let pool = PoolAddresses::new(7, Pubkey::new_unique(), Pubkey::new_unique())
+            .with_creator_fee_vaults();
+        let keys = BTreeMap::from([
+            ("config", pool.config),
+            ("mint_x", pool.mint_x),
+            ("mint_y", pool.mint_y),
+        ]);
+        let args = BTreeMap::from([("seed", 7u64.to_le_bytes().to_vec())]);
+
+        let idl = idl_accounts!(Initialize);
+        let address = |name| idl_address(&idl, name, &keys, &args);
+        let (creator_fee_x, creator_fee_y) = pool.creator_fee_vaults.unwrap();
+        assert_eq!(address("config"), pool.config);
+        assert_eq!(address("mint_lp"), pool.mint_lp);
+        assert_eq!(address("vault_x"), pool.vault_x);
+        assert_eq!(address("vault_y"), pool.vault_y);
+        assert_eq!(address("creator_fee_x"), creator_fee_x);
+        assert_eq!(address("creator_fee_y"), creator_fee_y);
+    }
+
+    #[test]
+    fn order_addresses_match_the_program_idl() {
+        let pool = PoolAddresses::new(7, Pubkey::new_unique(), Pubkey::new_unique());
+        let owner = Pubkey::new_unique();
+        let order = pool.order(&owner, 3);
+        let keys = BTreeMap::from([
+            ("config", pool.config),
+            ("owner", owner),
+            ("order", order.order),
+            ("mint_x", pool.mint_x),
+            ("mint_y", pool.mint_y),
+        ]);
+        let args = BTreeMap::from([("seed", 3u64.to_le_bytes().to_vec())]);
+
+        let idl = idl_accounts!(PlaceOrder);
+        let address = |name| idl_address(&idl, name, &keys, &args);
+        assert_eq!(address("order"), order.order);
+        assert_eq!(address("order_vault_x"), order.vault_x);
+        assert_eq!(address("order_vault_y"), order.vault_y);
+    }
+}
@@ -0,0 +1,249 @@
+use amm::Config;
+use anchor_lang::{prelude::Pubkey, solana_program::instruction::Instruction};
+use constant_product_curve::ConstantProduct;
+use solana_client::rpc_client::RpcClient;
+
+use crate::accounts::*;
+use crate::error::*;
+use crate::instructions;
+use crate::pda::*;
+
+// LP mint decimals fixed by `Initialize`
+const LP_DECIMALS: u32 = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reserves {
+    pub x: u64,
+    pub y: u64,
+    pub lp_supply: u64,
+}
+
+impl Reserves {
+    // Same curve call `deposit` makes on-chain, raised by `slippage_bps`
+    pub fn deposit_bounds(&self, amount: u64, slippage_bps: u16) -> Result<(u64, u64)> {
+        if self.lp_supply == 0 {
+            return Err(AmmClientError::EmptyPool);
+        }
+
+        let amounts = ConstantProduct::xy_deposit_amounts_from_l(
+            self.x,
+            self.y,
+            self.lp_supply,
+            amount,
+            LP_DECIMALS,
+        )?;
+
+        Ok((
+            with_slippage(amounts.x, slippage_bps, true)?,
+            with_slippage(amounts.y, slippage_bps, true)?,
+        ))
+    }
+
+    // Same curve call `withdraw` makes on-chain, lowered by `slippage_bps`
+    pub fn withdraw_bounds(&self, amount: u64, slippage_bps: u16) -> Result<(u64, u64)> {
+        if self.lp_supply == 0 {
+            return Err(AmmClientError::EmptyPool);
+        }
+
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            self.x,
+            self.y,
+            self.lp_supply,
+            amount,
+            LP_DECIMALS,
+        )?;
+
+        Ok((
+            with_slippage(amounts.x, slippage_bps, false)?,
+            with_slippage(amounts.y, slippage_bps, false)?,
+        ))
+    }
+}
+
+pub struct PoolClient {
+    pub rpc: RpcClient,
+    pub pool: PoolAddresses,
+}
+
+impl PoolClient {
+    // Loads the pool's config to learn its mints and whether it pays a creator fee
+    pub fn new(rpc: RpcClient, seed: u64) -> Result<PoolClient> {
+        let (config, _) = config_address(seed);
+        let config = decode_config(&rpc.get_account_data(&config)?)?;
+        let mut pool = PoolAddresses::new(seed, config.mint_x, config.mint_y);
+        if config.creator_fee != 0 {
+            pool = pool.with_creator_fee_vaults();
+        }
+        Ok(PoolClient { rpc, pool })
+    }
+
+    pub fn config(&self) -> Result<Config> {
+        decode_config(&self.rpc.get_account_data(&self.pool.config)?)
+    }
+
+    pub fn reserves(&self) -> Result<Reserves> {
+        let accounts = self.rpc.get_multiple_accounts(&[
+            self.pool.vault_x,
+            self.pool.vault_y,
+            self.pool.mint_lp,
+        ])?;
+        let data = |index: usize| -> Result<Vec<u8>> {
+            accounts[index]
+                .as_ref()
+                .map(|account| account.data.clone())
+                .ok_or(AmmClientError::EmptyPool)
+        };
+
+        Ok(Reserves {
+            x: decode_token_account(&data(0)?)?.amount,
+            y: decode_token_account(&data(1)?)?.amount,
+            lp_supply: decode_mint(&data(2)?)?.supply,
+        })
+    }
+
+    // Token amounts a deposit of `amount` LP needs right now, raised by `slippage_bps`
+    pub fn deposit_bounds(&self, amount: u64, slippage_bps: u16) -> Result<(u64, u64)> {
+        self.reserves()?.deposit_bounds(amount, slippage_bps)
+    }
+
+    // Token amounts burning `amount` LP returns right now, lowered by `slippage_bps`
+    pub fn withdraw_bounds(&self, amount: u64, slippage_bps: u16) -> Result<(u64, u64)> {
+        self.reserves()?.withdraw_bounds(amount, slippage_bps)
+    }
+
+    pub fn deposit(
+        &self,
+        lp_provider: &Pubkey,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<Instruction> {
+        let (max_x, max_y) = self.deposit_bounds(amount, slippage_bps)?;
+        Ok(instructions::deposit(
+            lp_provider,
+            &self.pool,
+            amount,
+            max_x,
+            max_y,
+        ))
+    }
+
+    pub fn withdraw(
+        &self,
+        lp_provider: &Pubkey,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<Instruction> {
+        let (min_x, min_y) = self.withdraw_bounds(amount, slippage_bps)?;
+        Ok(instructions::withdraw(
+            lp_provider,
+            &self.pool,
+            amount,
+            min_x,
+            min_y,
+        ))
+    }
+}
+
+// Widens `amount` by `slippage_bps` into a bound the caller accepts. An upper bound
+// (`up`) rounds up and a lower bound rounds down, so rounding never makes the bound
+// stricter than the requested slippage
+pub fn with_slippage(amount: u64, slippage_bps: u16, up: bool) -> Result<u64> {
+    if slippage_bps > 10_000 {
+        return Err(AmmClientError::InvalidSlippage);
+    }
+
+    let bps = match up {
+        true => 10_000 + slippage_bps as u128,
+        false => 10_000 - slippage_bps as u128,
+    };
+    let scaled = amount as u128 * bps;
+    let amount = match up {
+        true => scaled.div_ceil(10_000),
+        false => scaled / 10_000,
+    };
+    u64::try_from(amount).map_err(|_| AmmClientError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESERVES: Reserves = Reserves {
+        x: 1_000_000_000,
+        y: 2_000_000_000,
+        lp_supply: 1_000_000_000,
+    };
+
+    #[test]
+    fn upper_bounds_round_up_and_lower_bounds_round_down() {
+        // 10_001 * 1.0001 = 10_002.0001 and 10_001 * 0.9999 = 9_999.9999
+        assert_eq!(with_slippage(10_001, 1, true).unwrap(), 10_003);
+        assert_eq!(with_slippage(10_001, 1, false).unwrap(), 9_999);
+        assert_eq!(with_slippage(10_001, 0, true).unwrap(), 10_001);
+        assert_eq!(with_slippage(10_001, 0, false).unwrap(), 10_001);
+        assert_eq!(with_slippage(10_001, 10_000, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn out_of_range_slippage_is_rejected() {
+        assert!(matches!(
+            with_slippage(1, 10_001, false),
+            Err(AmmClientError::InvalidSlippage)
+        ));
+        assert!(matches!(
+            with_slippage(u64::MAX, 1, true),
+            Err(AmmClientError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn deposit_bounds_widen_the_amounts_the_program_charges() {
+        let amounts = ConstantProduct::xy_deposit_amounts_from_l(
+            RESERVES.x,
+            RESERVES.y,
+            RESERVES.lp_supply,
+            1_000_000,
+            LP_DECIMALS,
+        )
+        .unwrap();
+        let (max_x, max_y) = RESERVES.deposit_bounds(1_000_000, 50).unwrap();
+
+        assert!(max_x >= amounts.x && max_y >= amounts.y);
+        assert_eq!(max_x, with_slippage(amounts.x, 50, true).unwrap());
+        assert_eq!(max_y, with_slippage(amounts.y, 50, true).unwrap());
+    }
+
+    #[test]
+    fn withdraw_bounds_narrow_the_amounts_the_program_pays() {
+        let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
+            RESERVES.x,
+            RESERVES.y,
+            RESERVES.lp_supply,
+            1_000_000,
+            LP_DECIMALS,
+        )
+        .unwrap();
+        let (min_x, min_y) = RESERVES.withdraw_bounds(1_000_000, 50).unwrap();
+
+        assert!(min_x <= amounts.x && min_y <= amounts.y);
+        assert_eq!(min_x, with_slippage(amounts.x, 50, false).unwrap());
+        assert_eq!(min_y, with_slippage(amounts.y, 50, false).unwrap());
+    }
+
+    #[test]
+    fn empty_pools_have_no_quote() {
+        let empty = Reserves {
+            lp_supply: 0,
+            ..RESERVES
+        };
+
+        assert!(matches!(
+            empty.deposit_bounds(1, 0),
+            Err(AmmClientError::EmptyPool)
+        ));
+        assert!(matches!(
+            empty.withdraw_bounds(1, 0),
+            Err(AmmClientError::EmptyPool)
+        ));
+    }
+}
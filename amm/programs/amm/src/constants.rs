@@ -2,3 +2,13 @@ use anchor_lang::prelude::*;
 
 #[constant]
 pub const SEED: &str = "anchor";
+
+#[constant]
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+#[constant]
+pub const CONFIG_VERSION: u8 = 3;
+
+// Highest share of the swap fee a pool creator can take, in basis points
+#[constant]
+pub const MAX_CREATOR_FEE: u16 = 5_000;
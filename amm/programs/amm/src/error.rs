@@ -39,6 +39,28 @@ pub enum AmmError {
     InsufficientBalance,
     #[msg("Zero balance.")]
     ZeroBalance,
+    #[msg("Order price has not been crossed.")]
+    OrderNotCrossed,
+    #[msg("Invalid order account.")]
+    InvalidOrder,
+    #[msg("Nothing to claim.")]
+    NothingToClaim,
+    #[msg("Invalid config account.")]
+    InvalidConfig,
+    #[msg("Config is already on the current version.")]
+    AlreadyMigrated,
+    #[msg("Invalid oracle account.")]
+    InvalidOracle,
+    #[msg("Oracle price is stale.")]
+    StaleOracle,
+    #[msg("Pool price deviates too far from the oracle price.")]
+    PriceDeviationExceeded,
+    #[msg("Creator fee is above the allowed maximum.")]
+    CreatorFeeTooHigh,
+    #[msg("Creator fee vaults are required for this pool.")]
+    MissingCreatorFeeVault,
+    #[msg("Mock price feeds are only available in mock-oracle builds.")]
+    MockOracleDisabled,
 }
 
 impl From<CurveError> for AmmError {
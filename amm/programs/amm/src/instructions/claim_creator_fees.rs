@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+      has_one = mint_x,
+      has_one = mint_y,
+      has_one = creator,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_x.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_y.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = creator,
+      associated_token::mint = mint_x,
+      associated_token::authority = creator,
+    )]
+    pub creator_mint_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = creator,
+      associated_token::mint = mint_y,
+      associated_token::authority = creator,
+    )]
+    pub creator_mint_y: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimCreatorFees<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let (x, y) = (self.creator_fee_x.amount, self.creator_fee_y.amount);
+        require!(x != 0 || y != 0, AmmError::NothingToClaim);
+
+        if x != 0 {
+            self.withdraw_token(true, x)?;
+        }
+        if y != 0 {
+            self.withdraw_token(false, y)?;
+        }
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.creator_fee_x.to_account_info(),
+                self.creator_mint_x.to_account_info(),
+            ),
+            false => (
+                self.creator_fee_y.to_account_info(),
+                self.creator_mint_y.to_account_info(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(program, account, signer_seeds);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+}
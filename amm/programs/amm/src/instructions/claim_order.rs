@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ClaimOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+      has_one = mint_x,
+      has_one = mint_y,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        has_one = owner,
+        has_one = config,
+        seeds = [b"order", config.key().as_ref(), owner.key().as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = order,
+    )]
+    pub order_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = order,
+    )]
+    pub order_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = owner,
+      associated_token::mint = mint_x,
+      associated_token::authority = owner,
+    )]
+    pub owner_mint_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = owner,
+      associated_token::mint = mint_y,
+      associated_token::authority = owner,
+    )]
+    pub owner_mint_y: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimOrder<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let amount = self.order.claimable;
+        require!(amount != 0, AmmError::NothingToClaim);
+
+        // proceeds sit on the opposite side of what the order sells
+        self.withdraw_token(!self.order.is_x, amount)?;
+
+        self.order.claimable = 0;
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.order_vault_x.to_account_info(),
+                self.owner_mint_x.to_account_info(),
+            ),
+            false => (
+                self.order_vault_y.to_account_info(),
+                self.owner_mint_y.to_account_info(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to,
+            authority: self.order.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let seed = self.order.seed.to_le_bytes();
+        let seeds = &[
+            &b"order"[..],
+            config_key.as_ref(),
+            self.order.owner.as_ref(),
+            &seed,
+            &[self.order.bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(program, account, signer_seeds);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+}
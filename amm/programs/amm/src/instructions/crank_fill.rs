@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+use constant_product_curve::{ConstantProduct, CurveError, LiquidityPair};
+
+use crate::error::*;
+use crate::oracle::check_price_deviation;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CrankFill<'info> {
+    pub cranker: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+      has_one = mint_x,
+      has_one = mint_y,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        has_one = config,
+        seeds = [b"order", config.key().as_ref(), order.owner.as_ref(), order.seed.to_le_bytes().as_ref()],
+        bump = order.bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = order,
+    )]
+    pub order_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = order,
+    )]
+    pub order_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_x.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_x: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_y.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_y: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: matched against `config.oracle` and parsed in `check_price_deviation`
+    pub oracle: Option<UncheckedAccount<'info>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CrankFill<'info> {
+    pub fn crank_fill(&mut self) -> Result<()> {
+        require!(self.config.locked == false, AmmError::PoolLocked);
+
+        let order_vault_x = self.order_vault_x.to_account_info();
+        let order_vault_y = self.order_vault_y.to_account_info();
+
+        let mut pool = OrderFill {
+            config: &self.config,
+            mint_lp: &self.mint_lp,
+            vault_x: &mut self.vault_x,
+            vault_y: &mut self.vault_y,
+            creator_fee_x: self.creator_fee_x.as_ref().map(|v| v.to_account_info()),
+            creator_fee_y: self.creator_fee_y.as_ref().map(|v| v.to_account_info()),
+            token_program: &self.token_program,
+        };
+        let filled = pool.fill(&mut self.order, order_vault_x, order_vault_y)?;
+
+        require!(filled, AmmError::OrderNotCrossed);
+
+        check_price_deviation(
+            &self.config,
+            self.oracle.as_deref(),
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )
+    }
+}
+
+// Pool accounts needed to fill a resting order against the curve
+pub struct OrderFill<'a, 'info> {
+    pub config: &'a Account<'info, Config>,
+    pub mint_lp: &'a Account<'info, Mint>,
+    pub vault_x: &'a mut Box<Account<'info, TokenAccount>>,
+    pub vault_y: &'a mut Box<Account<'info, TokenAccount>>,
+    pub creator_fee_x: Option<AccountInfo<'info>>,
+    pub creator_fee_y: Option<AccountInfo<'info>>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'a, 'info> OrderFill<'a, 'info> {
+    // Sells the whole resting amount into the pool if the curve pays at least the order
+    // price. Returns false without moving funds when the price has not been crossed.
+    pub fn fill(
+        &mut self,
+        order: &mut Account<'info, Order>,
+        order_vault_x: AccountInfo<'info>,
+        order_vault_y: AccountInfo<'info>,
+    ) -> Result<bool> {
+        if order.amount == 0 {
+            return Ok(false);
+        }
+
+        let min_out = order.min_out(order.amount).ok_or(AmmError::Overflow)?;
+
+        let mut curve = ConstantProduct::init(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            self.config.fee,
+            None,
+        )
+        .map_err(AmmError::from)?;
+
+        let pair = match order.is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
+        };
+        let res = match curve.swap(pair, order.amount, min_out) {
+            Ok(res) => res,
+            Err(CurveError::SlippageLimitExceeded) => return Ok(false),
+            Err(error) => return Err(AmmError::from(error).into()),
+        };
+
+        let creator_cut = self.config.creator_cut(res.fee).ok_or(AmmError::Overflow)?;
+        let pool_deposit = res
+            .deposit
+            .checked_sub(creator_cut)
+            .ok_or(AmmError::Overflow)?;
+
+        let (order_in, order_out, pool_in, pool_out, creator_fee) = match order.is_x {
+            true => (
+                order_vault_x,
+                order_vault_y,
+                self.vault_x.to_account_info(),
+                self.vault_y.to_account_info(),
+                self.creator_fee_x.clone(),
+            ),
+            false => (
+                order_vault_y,
+                order_vault_x,
+                self.vault_y.to_account_info(),
+                self.vault_x.to_account_info(),
+                self.creator_fee_y.clone(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+
+        // order tokens into the pool, less the creator's cut of the fee
+        let account = Transfer {
+            from: order_in.clone(),
+            to: pool_in,
+            authority: order.to_account_info(),
+        };
+
+        let config_key = self.config.key();
+        let owner_key = order.owner;
+        let seed = order.seed.to_le_bytes();
+        let seeds = &[
+            &b"order"[..],
+            config_key.as_ref(),
+            owner_key.as_ref(),
+            &seed,
+            &[order.bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(program.clone(), account, signer_seeds);
+        transfer(ctx, pool_deposit)?;
+
+        if creator_cut != 0 {
+            let account = Transfer {
+                from: order_in,
+                to: creator_fee.ok_or(AmmError::MissingCreatorFeeVault)?,
+                authority: order.to_account_info(),
+            };
+
+            let ctx = CpiContext::new_with_signer(program.clone(), account, signer_seeds);
+            transfer(ctx, creator_cut)?;
+        }
+
+        // pool tokens into the order's claimable vault
+        let account = Transfer {
+            from: pool_out,
+            to: order_out,
+            authority: self.config.to_account_info(),
+        };
+
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(program, account, signer_seeds);
+        transfer(ctx, res.withdraw)?;
+
+        order.amount = order
+            .amount
+            .checked_sub(res.deposit)
+            .ok_or(AmmError::Underflow)?;
+        order.claimable = order
+            .claimable
+            .checked_add(res.withdraw)
+            .ok_or(AmmError::Overflow)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        Ok(true)
+    }
+}
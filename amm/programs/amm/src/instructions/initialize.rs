@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, token::{Mint, Token, TokenAccount}};
 
+use crate::constants::{CONFIG_VERSION, MAX_CREATOR_FEE};
+use crate::error::*;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -8,8 +10,8 @@ use crate::state::*;
 pub struct Initialize<'info> {
     #[account(mut)]
     pub initializer: Signer<'info>,
-    pub mint_x: Account<'info, Mint>,
-    pub mint_y: Account<'info, Mint>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
     #[account(
         init,
         payer = initializer,
@@ -18,21 +20,21 @@ pub struct Initialize<'info> {
         seeds = [b"lp", config.key.as_ref()], 
         bump
     )]
-    pub mint_lp: Account<'info, Mint>,
+    pub mint_lp: Box<Account<'info, Mint>>,
     #[account(
         init,
         payer = initializer,
         associated_token::mint = mint_x,
         associated_token::authority = config,
     )]
-    pub vault_x: Account<'info, TokenAccount>,
+    pub vault_x: Box<Account<'info, TokenAccount>>,
     #[account(
         init,
         payer = initializer,
         associated_token::mint = mint_y,
         associated_token::authority = config,
     )]
-    pub vault_y: Account<'info, TokenAccount>,
+    pub vault_y: Box<Account<'info, TokenAccount>>,
     #[account(
         init,
         payer = initializer,
@@ -40,14 +42,34 @@ pub struct Initialize<'info> {
         seeds = [b"config", seed.to_le_bytes().as_ref()], 
         bump
     )]
-    pub config : Account<'info, Config>,
+    pub config : Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = initializer,
+        token::mint = mint_x,
+        token::authority = config,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_x.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = initializer,
+        token::mint = mint_y,
+        token::authority = config,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_y.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_y: Box<Account<'info, TokenAccount>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info>Initialize<'info> {
-    pub fn init(&mut self, seed: u64, bumps: InitializeBumps, fee: u16, authority: Option<Pubkey>) -> Result<()> {
+    pub fn init(&mut self, seed: u64, bumps: InitializeBumps, fee: u16, authority: Option<Pubkey>, creator_fee: u16) -> Result<()> {
+        require!(creator_fee <= MAX_CREATOR_FEE, AmmError::CreatorFeeTooHigh);
+
         self.config.set_inner(Config {
             seed,
             authority,
@@ -56,7 +78,14 @@ impl<'info>Initialize<'info> {
             fee,
             locked: false,
             config_bump: bumps.config,
-            lp_bump: bumps.mint_lp
+            lp_bump: bumps.mint_lp,
+            version: CONFIG_VERSION,
+            oracle: Pubkey::default(),
+            max_deviation_bps: 0,
+            max_oracle_age: 0,
+            creator: self.initializer.key(),
+            creator_fee,
+            reserved: [0; 56],
         });
         Ok(())
     }
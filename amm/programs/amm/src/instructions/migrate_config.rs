@@ -0,0 +1,100 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+    Discriminator,
+};
+
+use crate::constants::CONFIG_VERSION;
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: may still hold an older Config layout, parsed and validated in `migrate`
+    #[account(mut, owner = crate::ID)]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateConfig<'info> {
+    pub fn migrate(&mut self) -> Result<()> {
+        let mut config = self.load()?;
+
+        require!(config.version < CONFIG_VERSION, AmmError::AlreadyMigrated);
+        match config.authority {
+            Some(authority) => require_keys_eq!(
+                authority,
+                self.authority.key(),
+                AmmError::InvalidAuthority
+            ),
+            None => return err!(AmmError::NoAuthoritySet),
+        }
+
+        let expected = Pubkey::create_program_address(
+            &[
+                &b"config"[..],
+                &config.seed.to_le_bytes(),
+                &[config.config_bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidConfig)?;
+        require_keys_eq!(expected, self.config.key(), AmmError::InvalidConfig);
+
+        self.resize()?;
+
+        // fields introduced after the stored version start from their zeroed defaults
+        config.version = CONFIG_VERSION;
+
+        let mut data = self.config.try_borrow_mut_data()?;
+        config.try_serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    // Reads the config in whichever layout it was written with
+    pub fn load(&self) -> Result<Config> {
+        let data = self.config.try_borrow_data()?;
+
+        require!(
+            data.len() >= 8 && data[..8] == Config::DISCRIMINATOR,
+            AmmError::InvalidConfig
+        );
+
+        match data.len() < Config::INIT_SPACE {
+            true => {
+                let legacy = ConfigV0::deserialize(&mut &data[8..])
+                    .map_err(|_| AmmError::InvalidConfig)?;
+                Ok(Config::from(legacy))
+            }
+            false => Ok(Config::try_deserialize(&mut &data[..])?),
+        }
+    }
+
+    // Grows the account to the current layout, with the authority covering the extra rent
+    pub fn resize(&mut self) -> Result<()> {
+        let config = self.config.to_account_info();
+        if config.data_len() >= Config::INIT_SPACE {
+            return Ok(());
+        }
+
+        let rent = Rent::get()?.minimum_balance(Config::INIT_SPACE);
+        let lamports = rent.saturating_sub(config.lamports());
+
+        if lamports != 0 {
+            let program = self.system_program.to_account_info();
+            let accounts = Transfer {
+                from: self.authority.to_account_info(),
+                to: config.clone(),
+            };
+
+            let ctx = CpiContext::new(program, accounts);
+
+            transfer(ctx, lamports)?;
+        }
+
+        config.realloc(Config::INIT_SPACE, true)?;
+        Ok(())
+    }
+}
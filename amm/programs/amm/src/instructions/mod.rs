@@ -1,7 +1,25 @@
+pub mod cancel_order;
+pub mod claim_creator_fees;
+pub mod claim_order;
+pub mod crank_fill;
 pub mod deposit;
 pub mod initialize;
+pub mod migrate_config;
+pub mod place_order;
+pub mod set_mock_feed;
+pub mod swap;
+pub mod update_oracle;
 pub mod withdraw;
 
+pub use cancel_order::*;
+pub use claim_creator_fees::*;
+pub use claim_order::*;
+pub use crank_fill::*;
 pub use deposit::*;
 pub use initialize::*;
+pub use migrate_config::*;
+pub use place_order::*;
+pub use set_mock_feed::*;
+pub use swap::*;
+pub use update_oracle::*;
 pub use withdraw::*;
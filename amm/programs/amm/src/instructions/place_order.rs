@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+      has_one = mint_x,
+      has_one = mint_y,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = owner,
+        space = Order::INIT_SPACE,
+        seeds = [b"order", config.key().as_ref(), owner.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_x,
+        associated_token::authority = order,
+    )]
+    pub order_vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = mint_y,
+        associated_token::authority = order,
+    )]
+    pub order_vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = owner,
+      associated_token::mint = mint_x,
+      associated_token::authority = owner,
+    )]
+    pub owner_mint_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = owner,
+      associated_token::mint = mint_y,
+      associated_token::authority = owner,
+    )]
+    pub owner_mint_y: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    pub fn place_order(
+        &mut self,
+        seed: u64,
+        is_x: bool,
+        amount: u64,
+        price: u64,
+        bumps: &PlaceOrderBumps,
+    ) -> Result<()> {
+        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(amount != 0 && price != 0, AmmError::InvalidAmount);
+
+        self.order.set_inner(Order {
+            owner: self.owner.key(),
+            config: self.config.key(),
+            seed,
+            is_x,
+            amount,
+            price,
+            claimable: 0,
+            bump: bumps.order,
+        });
+
+        self.deposit_token(is_x, amount)
+    }
+
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.owner_mint_x.to_account_info(),
+                self.order_vault_x.to_account_info(),
+            ),
+            false => (
+                self.owner_mint_y.to_account_info(),
+                self.order_vault_y.to_account_info(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to,
+            authority: self.owner.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(program, account);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+}
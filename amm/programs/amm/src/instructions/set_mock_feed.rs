@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SetMockFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MockFeed::INIT_SPACE,
+        seeds = [b"mock_feed", authority.key().as_ref()],
+        bump
+    )]
+    pub feed: Account<'info, MockFeed>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetMockFeed<'info> {
+    // Anchor 0.30 can't cfg out an instruction, so builds without the feature reject it
+    #[cfg(not(feature = "mock-oracle"))]
+    pub fn set_price(&mut self, _price: u64, _bumps: &SetMockFeedBumps) -> Result<()> {
+        err!(AmmError::MockOracleDisabled)
+    }
+
+    #[cfg(feature = "mock-oracle")]
+    pub fn set_price(&mut self, price: u64, bumps: &SetMockFeedBumps) -> Result<()> {
+        self.feed.set_inner(MockFeed {
+            authority: self.authority.key(),
+            price,
+            publish_time: Clock::get()?.unix_timestamp,
+            bump: bumps.feed,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{get_associated_token_address, AssociatedToken},
+    token::{transfer, Mint, Token, TokenAccount, Transfer},
+};
+use constant_product_curve::{ConstantProduct, LiquidityPair};
+
+use crate::error::*;
+use crate::instructions::OrderFill;
+use crate::oracle::check_price_deviation;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub mint_x: Box<Account<'info, Mint>>,
+    pub mint_y: Box<Account<'info, Mint>>,
+    #[account(
+      has_one = mint_x,
+      has_one = mint_y,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [b"lp", config.key().as_ref()],
+        bump = config.lp_bump
+    )]
+    pub mint_lp: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_x,
+        associated_token::authority = config,
+    )]
+    pub vault_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = mint_y,
+        associated_token::authority = config,
+    )]
+    pub vault_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = user,
+      associated_token::mint = mint_x,
+      associated_token::authority = user,
+    )]
+    pub user_mint_x: Box<Account<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = user,
+      associated_token::mint = mint_y,
+      associated_token::authority = user,
+    )]
+    pub user_mint_y: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_x.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_x: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [b"creator_fee", config.key().as_ref(), mint_y.key().as_ref()],
+        bump
+    )]
+    pub creator_fee_y: Option<Box<Account<'info, TokenAccount>>>,
+    /// CHECK: matched against `config.oracle` and parsed in `check_price_deviation`
+    pub oracle: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Swap<'info> {
+    pub fn swap(&mut self, is_x: bool, amount: u64, min: u64) -> Result<()> {
+        require!(self.config.locked == false, AmmError::PoolLocked);
+        require!(amount != 0, AmmError::InvalidAmount);
+
+        let mut curve = ConstantProduct::init(
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_lp.supply,
+            self.config.fee,
+            None,
+        )
+        .map_err(AmmError::from)?;
+
+        let pair = match is_x {
+            true => LiquidityPair::X,
+            false => LiquidityPair::Y,
+        };
+        let res = curve.swap(pair, amount, min).map_err(AmmError::from)?;
+
+        require!(res.deposit != 0 && res.withdraw != 0, AmmError::InvalidAmount);
+
+        let creator_cut = self.config.creator_cut(res.fee).ok_or(AmmError::Overflow)?;
+        let pool_deposit = res
+            .deposit
+            .checked_sub(creator_cut)
+            .ok_or(AmmError::Overflow)?;
+
+        // deposit the sold token, less the creator's cut of the fee
+        self.deposit_token(is_x, pool_deposit)?;
+        if creator_cut != 0 {
+            self.deposit_creator_fee(is_x, creator_cut)?;
+        }
+        // withdraw the bought token
+        self.withdraw_token(!is_x, res.withdraw)?;
+
+        self.vault_x.reload()?;
+        self.vault_y.reload()?;
+        Ok(())
+    }
+
+    // Fills every order passed as [order, order_vault_x, order_vault_y] whose price the
+    // swap has crossed. Orders that are still out of the money are left untouched.
+    pub fn fill_orders(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(remaining_accounts.len() % 3 == 0, AmmError::InvalidOrder);
+
+        let mut pool = OrderFill {
+            config: &self.config,
+            mint_lp: &self.mint_lp,
+            vault_x: &mut self.vault_x,
+            vault_y: &mut self.vault_y,
+            creator_fee_x: self.creator_fee_x.as_ref().map(|v| v.to_account_info()),
+            creator_fee_y: self.creator_fee_y.as_ref().map(|v| v.to_account_info()),
+            token_program: &self.token_program,
+        };
+
+        for accounts in remaining_accounts.chunks(3) {
+            let mut order = Account::<Order>::try_from(&accounts[0])?;
+            let (order_vault_x, order_vault_y) = (&accounts[1], &accounts[2]);
+
+            require_keys_eq!(order.config, pool.config.key(), AmmError::InvalidOrder);
+            require!(accounts[0].is_writable, AmmError::InvalidOrder);
+            require_keys_eq!(
+                order_vault_x.key(),
+                get_associated_token_address(&order.key(), &pool.config.mint_x),
+                AmmError::InvalidOrder
+            );
+            require_keys_eq!(
+                order_vault_y.key(),
+                get_associated_token_address(&order.key(), &pool.config.mint_y),
+                AmmError::InvalidOrder
+            );
+
+            if pool.fill(&mut order, order_vault_x.clone(), order_vault_y.clone())? {
+                order.exit(&crate::ID)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn check_oracle(&self) -> Result<()> {
+        check_price_deviation(
+            &self.config,
+            self.oracle.as_deref(),
+            self.vault_x.amount,
+            self.vault_y.amount,
+            self.mint_x.decimals,
+            self.mint_y.decimals,
+        )
+    }
+
+    pub fn deposit_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.user_mint_x.to_account_info(),
+                self.vault_x.to_account_info(),
+            ),
+            false => (
+                self.user_mint_y.to_account_info(),
+                self.vault_y.to_account_info(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(program, account);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+
+    pub fn deposit_creator_fee(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.user_mint_x.to_account_info(),
+                self.creator_fee_x.as_ref().map(|v| v.to_account_info()),
+            ),
+            false => (
+                self.user_mint_y.to_account_info(),
+                self.creator_fee_y.as_ref().map(|v| v.to_account_info()),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to: to.ok_or(AmmError::MissingCreatorFeeVault)?,
+            authority: self.user.to_account_info(),
+        };
+
+        let ctx = CpiContext::new(program, account);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+
+    pub fn withdraw_token(&mut self, is_x: bool, amount: u64) -> Result<()> {
+        let (from, to) = match is_x {
+            true => (
+                self.vault_x.to_account_info(),
+                self.user_mint_x.to_account_info(),
+            ),
+            false => (
+                self.vault_y.to_account_info(),
+                self.user_mint_y.to_account_info(),
+            ),
+        };
+        let program = self.token_program.to_account_info();
+        let account = Transfer {
+            from,
+            to,
+            authority: self.config.to_account_info(),
+        };
+
+        let seeds = &[
+            &b"config"[..],
+            &self.config.seed.to_le_bytes(),
+            &[self.config.config_bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let ctx = CpiContext::new_with_signer(program, account, signer_seeds);
+
+        transfer(ctx, amount)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::error::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateOracle<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+      mut,
+      constraint = config.authority.is_some() @ AmmError::NoAuthoritySet,
+      constraint = config.authority == Some(authority.key()) @ AmmError::InvalidAuthority,
+      seeds = [b"config", config.seed.to_le_bytes().as_ref()],
+      bump = config.config_bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+impl<'info> UpdateOracle<'info> {
+    // Passing the default key as `oracle` turns the guard off
+    pub fn update_oracle(
+        &mut self,
+        oracle: Pubkey,
+        max_deviation_bps: u16,
+        max_oracle_age: u32,
+    ) -> Result<()> {
+        if oracle != Pubkey::default() {
+            require!(
+                max_deviation_bps != 0 && max_deviation_bps <= 10_000,
+                AmmError::InvalidAmount
+            );
+        }
+
+        self.config.oracle = oracle;
+        self.config.max_deviation_bps = max_deviation_bps;
+        self.config.max_oracle_age = max_oracle_age;
+        Ok(())
+    }
+}
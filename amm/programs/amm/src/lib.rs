@@ -1,6 +1,7 @@
 pub mod constants;
 pub mod error;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
 
 use anchor_lang::prelude::*;
@@ -20,8 +21,10 @@ pub mod amm {
         seed: u64,
         fee: u16,
         authority: Option<Pubkey>,
+        creator_fee: u16,
     ) -> Result<()> {
-        ctx.accounts.init(seed, ctx.bumps, fee, authority)
+        ctx.accounts
+            .init(seed, ctx.bumps, fee, authority, creator_fee)
     }
 
     pub fn deposit(ctx: Context<Deposit>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
@@ -31,4 +34,60 @@ pub mod amm {
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64, max_x: u64, max_y: u64) -> Result<()> {
         ctx.accounts.withdraw(amount, max_x, max_y)
     }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        ctx.accounts.migrate()
+    }
+
+    pub fn update_oracle(
+        ctx: Context<UpdateOracle>,
+        oracle: Pubkey,
+        max_deviation_bps: u16,
+        max_oracle_age: u32,
+    ) -> Result<()> {
+        ctx.accounts
+            .update_oracle(oracle, max_deviation_bps, max_oracle_age)
+    }
+
+    pub fn set_mock_feed(ctx: Context<SetMockFeed>, price: u64) -> Result<()> {
+        ctx.accounts.set_price(price, &ctx.bumps)
+    }
+
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        is_x: bool,
+        amount: u64,
+        min: u64,
+    ) -> Result<()> {
+        ctx.accounts.swap(is_x, amount, min)?;
+        ctx.accounts.fill_orders(ctx.remaining_accounts)?;
+        ctx.accounts.check_oracle()
+    }
+
+    pub fn place_order(
+        ctx: Context<PlaceOrder>,
+        seed: u64,
+        is_x: bool,
+        amount: u64,
+        price: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .place_order(seed, is_x, amount, price, &ctx.bumps)
+    }
+
+    pub fn crank_fill(ctx: Context<CrankFill>) -> Result<()> {
+        ctx.accounts.crank_fill()
+    }
+
+    pub fn claim_order(ctx: Context<ClaimOrder>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        ctx.accounts.cancel()
+    }
 }
@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::PRICE_SCALE;
+use crate::error::*;
+use crate::state::*;
+
+// Owner of Pyth pull-oracle price accounts
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+// Price of one whole token X in whole tokens Y, `price * 10^exponent`
+pub struct OraclePrice {
+    pub price: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+// Reads a price out of an external feed account. Implement this for each feed layout a
+// pool may point at and add it to `load_price`, checked against the feed's owner.
+pub trait PriceFeedParser {
+    fn parse(account: &AccountInfo) -> Result<OraclePrice>;
+}
+
+// Pyth's `PriceUpdateV2` account, mirrored from pyth-solana-receiver-sdk, which pins an
+// older solana-program than Anchor 0.30
+#[derive(AnchorDeserialize)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub const DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+}
+
+#[derive(AnchorDeserialize, PartialEq)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+impl PriceFeedParser for PriceUpdateV2 {
+    fn parse(account: &AccountInfo) -> Result<OraclePrice> {
+        let data = account.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == PriceUpdateV2::DISCRIMINATOR,
+            AmmError::InvalidOracle
+        );
+
+        let update =
+            PriceUpdateV2::deserialize(&mut &data[8..]).map_err(|_| AmmError::InvalidOracle)?;
+        // Partially verified updates carry fewer guardian signatures than Pyth requires
+        require!(
+            update.verification_level == VerificationLevel::Full,
+            AmmError::InvalidOracle
+        );
+
+        let message = update.price_message;
+        Ok(OraclePrice {
+            price: u64::try_from(message.price).map_err(|_| AmmError::InvalidOracle)?,
+            exponent: message.exponent,
+            publish_time: message.publish_time,
+        })
+    }
+}
+
+#[cfg(feature = "mock-oracle")]
+impl PriceFeedParser for MockFeed {
+    fn parse(account: &AccountInfo) -> Result<OraclePrice> {
+        let feed = MockFeed::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        Ok(OraclePrice {
+            price: feed.price,
+            exponent: -(PRICE_SCALE.ilog10() as i32),
+            publish_time: feed.publish_time,
+        })
+    }
+}
+
+pub fn load_price(account: &AccountInfo) -> Result<OraclePrice> {
+    if *account.owner == PYTH_RECEIVER_ID {
+        return PriceUpdateV2::parse(account);
+    }
+    #[cfg(feature = "mock-oracle")]
+    if *account.owner == crate::ID {
+        return MockFeed::parse(account);
+    }
+    err!(AmmError::InvalidOracle)
+}
+
+// Oracle price in raw units of token Y per raw unit of token X, scaled by PRICE_SCALE like
+// the pool price it's compared against
+pub fn scaled_price(feed: &OraclePrice, decimals_x: u8, decimals_y: u8) -> Option<u128> {
+    let exponent =
+        feed.exponent + PRICE_SCALE.ilog10() as i32 + decimals_y as i32 - decimals_x as i32;
+    let price = feed.price as u128;
+
+    match exponent >= 0 {
+        true => price.checked_mul(10u128.checked_pow(exponent as u32)?),
+        false => Some(price / 10u128.checked_pow(exponent.unsigned_abs())?),
+    }
+}
+
+// Rejects the trade if the pool price after it strays from the oracle price by more than
+// the configured band. Pools without an oracle are not checked.
+pub fn check_price_deviation(
+    config: &Config,
+    oracle: Option<&AccountInfo>,
+    x: u64,
+    y: u64,
+    decimals_x: u8,
+    decimals_y: u8,
+) -> Result<()> {
+    if config.oracle == Pubkey::default() {
+        return Ok(());
+    }
+
+    let oracle = oracle.ok_or(AmmError::InvalidOracle)?;
+    require_keys_eq!(oracle.key(), config.oracle, AmmError::InvalidOracle);
+
+    let feed = load_price(oracle)?;
+    let oracle_price = scaled_price(&feed, decimals_x, decimals_y).ok_or(AmmError::Overflow)?;
+    require!(oracle_price != 0, AmmError::InvalidOracle);
+
+    if config.max_oracle_age != 0 {
+        let age = Clock::get()?
+            .unix_timestamp
+            .saturating_sub(feed.publish_time);
+        require!(age <= config.max_oracle_age as i64, AmmError::StaleOracle);
+    }
+
+    require!(x != 0, AmmError::NoLiquidityInPool);
+    let pool_price = (y as u128)
+        .checked_mul(PRICE_SCALE as u128)
+        .ok_or(AmmError::Overflow)?
+        / x as u128;
+
+    let deviation = pool_price
+        .abs_diff(oracle_price)
+        .checked_mul(10_000)
+        .ok_or(AmmError::Overflow)?
+        / oracle_price;
+
+    require!(
+        deviation <= config.max_deviation_bps as u128,
+        AmmError::PriceDeviationExceeded
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_update(full: bool, price: i64, exponent: i32) -> Vec<u8> {
+        let mut data = PriceUpdateV2::DISCRIMINATOR.to_vec();
+        data.extend([0; 32]);
+        match full {
+            true => data.push(1),
+            false => data.extend([0, 3]),
+        }
+        data.extend([0; 32]);
+        data.extend(price.to_le_bytes());
+        data.extend(0u64.to_le_bytes());
+        data.extend(exponent.to_le_bytes());
+        data.extend(100i64.to_le_bytes());
+        data.extend([0; 8 * 4]);
+        data.extend(0u64.to_le_bytes());
+        data
+    }
+
+    fn parse(owner: &Pubkey, mut data: Vec<u8>) -> Result<OraclePrice> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            owner,
+            false,
+            0,
+        );
+        load_price(&account)
+    }
+
+    #[test]
+    fn pyth_prices_are_scaled_to_raw_pool_units() {
+        // 150 USDC per SOL
+        let feed = parse(&PYTH_RECEIVER_ID, price_update(true, 15_000_000_000, -8)).unwrap();
+
+        assert_eq!(
+            (feed.price, feed.exponent, feed.publish_time),
+            (15_000_000_000, -8, 100)
+        );
+        assert_eq!(scaled_price(&feed, 9, 6), Some(150_000_000));
+    }
+
+    #[test]
+    fn partially_verified_or_negative_pyth_prices_are_rejected() {
+        assert!(parse(&PYTH_RECEIVER_ID, price_update(false, 15_000_000_000, -8)).is_err());
+        assert!(parse(&PYTH_RECEIVER_ID, price_update(true, -1, -8)).is_err());
+    }
+
+    #[test]
+    fn feeds_from_other_owners_are_rejected() {
+        let data = price_update(true, 15_000_000_000, -8);
+
+        assert!(parse(&Pubkey::new_unique(), data).is_err());
+    }
+}
@@ -10,8 +10,60 @@ pub struct Config {
     pub locked: bool,
     pub config_bump: u8,
     pub lp_bump: u8,
+    pub version: u8,            // Layout version, see CONFIG_VERSION
+    pub oracle: Pubkey,         // Price feed guarding swaps, default key when disabled
+    pub max_deviation_bps: u16, // Allowed pool price deviation from the oracle
+    pub max_oracle_age: u32,    // Max feed age in seconds, 0 to skip the check
+    pub creator: Pubkey,        // Pool initializer, paid the creator fee
+    pub creator_fee: u16,       // Creator share of each swap fee in basis points
+    pub reserved: [u8; 56],     // Zeroed space for fields added by later versions
 }
 
 impl Space for Config {
-    const INIT_SPACE: usize = 8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1 + 1;
+    const INIT_SPACE: usize =
+        8 + 8 + (1 + 32) + 32 + 32 + 2 + 1 + 1 + 1 + 1 + 32 + 2 + 4 + 32 + 2 + 56;
+}
+
+impl Config {
+    // Part of a swap fee routed to the creator instead of the pool
+    pub fn creator_cut(&self, fee: u64) -> Option<u64> {
+        let cut = (fee as u128).checked_mul(self.creator_fee as u128)? / 10_000;
+        u64::try_from(cut).ok()
+    }
+}
+
+// Layout of pools created before Config carried a version, kept so `migrate_config`
+// can read them
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ConfigV0 {
+    pub seed: u64,
+    pub authority: Option<Pubkey>,
+    pub mint_x: Pubkey,
+    pub mint_y: Pubkey,
+    pub fee: u16,
+    pub locked: bool,
+    pub config_bump: u8,
+    pub lp_bump: u8,
+}
+
+impl From<ConfigV0> for Config {
+    fn from(config: ConfigV0) -> Config {
+        Config {
+            seed: config.seed,
+            authority: config.authority,
+            mint_x: config.mint_x,
+            mint_y: config.mint_y,
+            fee: config.fee,
+            locked: config.locked,
+            config_bump: config.config_bump,
+            lp_bump: config.lp_bump,
+            version: 0,
+            oracle: Pubkey::default(),
+            max_deviation_bps: 0,
+            max_oracle_age: 0,
+            creator: Pubkey::default(),
+            creator_fee: 0,
+            reserved: [0; 56],
+        }
+    }
 }
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+// Authority-written price feed for local testing of the oracle guard. Only read by
+// builds with the `mock-oracle` feature
+#[account]
+pub struct MockFeed {
+    pub authority: Pubkey,
+    pub price: u64, // Whole tokens Y per whole token X, scaled by PRICE_SCALE
+    pub publish_time: i64,
+    pub bump: u8,
+}
+
+impl Space for MockFeed {
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + 1;
+}
@@ -1,2 +1,6 @@
 pub mod config;
+pub mod mock_feed;
+pub mod order;
 pub use config::*;
+pub use mock_feed::*;
+pub use order::*;
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::PRICE_SCALE;
+
+#[account]
+pub struct Order {
+    pub owner: Pubkey,
+    pub config: Pubkey,
+    pub seed: u64,
+    pub is_x: bool,     // Selling token X for token Y when true
+    pub amount: u64,    // Sell-side tokens still resting in the order
+    pub price: u64,     // Minimum buy-side tokens per sell-side token, scaled by PRICE_SCALE
+    pub claimable: u64, // Filled buy-side tokens waiting to be claimed
+    pub bump: u8,
+}
+
+impl Space for Order {
+    const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 1 + 8 + 8 + 8 + 1;
+}
+
+impl Order {
+    // Smallest amount of buy-side tokens that satisfies the order price for `amount`
+    pub fn min_out(&self, amount: u64) -> Option<u64> {
+        let out = (amount as u128)
+            .checked_mul(self.price as u128)?
+            .checked_add(PRICE_SCALE as u128 - 1)?
+            / PRICE_SCALE as u128;
+        u64::try_from(out).ok()
+    }
+}
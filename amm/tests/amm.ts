@@ -1,16 +1,425 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { createAssociatedTokenAccountIdempotentInstruction, createInitializeMint2Instruction, createMintToInstruction, getAssociatedTokenAddressSync, getMinimumBalanceForRentExemptMint, MINT_SIZE, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { assert } from "chai";
+import * as fs from "fs";
 import { Amm } from "../target/types/amm";
 
 describe("amm", () => {
-  // Configure the client to use the local cluster.
   anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.getProvider();
 
   const program = anchor.workspace.Amm as Program<Amm>;
+  const tokenProgram = TOKEN_PROGRAM_ID;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
-  });
+  const alice = anchor.web3.Keypair.generate(); // pool creator and authority
+  const bob = anchor.web3.Keypair.generate();
+  const mintX = anchor.web3.Keypair.generate();
+  const mintY = anchor.web3.Keypair.generate();
+
+  const PRICE_SCALE = new BN(1_000_000_000)
+  const tokenAmount = 10_000_000_000
+  const liquidity = new BN(1_000_000_000) // 1000 of each token at a 1:1 price
+  const fee = 30
+  const creatorFee = 2_000
+
+  const ata = (mint: PublicKey, owner: PublicKey) => getAssociatedTokenAddressSync(mint, owner, true, tokenProgram);
+  const balance = async (account: PublicKey) => new BN((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+  const poolAccounts = (seed: BN) => {
+    const [config] = PublicKey.findProgramAddressSync([Buffer.from("config"), seed.toArrayLike(Buffer, "le", 8)], program.programId);
+    const [mintLp] = PublicKey.findProgramAddressSync([Buffer.from("lp"), config.toBuffer()], program.programId);
+    const [creatorFeeX] = PublicKey.findProgramAddressSync([Buffer.from("creator_fee"), config.toBuffer(), mintX.publicKey.toBuffer()], program.programId);
+    const [creatorFeeY] = PublicKey.findProgramAddressSync([Buffer.from("creator_fee"), config.toBuffer(), mintY.publicKey.toBuffer()], program.programId);
+
+    return {
+      mintX: mintX.publicKey,
+      mintY: mintY.publicKey,
+      config,
+      mintLp,
+      vaultX: ata(mintX.publicKey, config),
+      vaultY: ata(mintY.publicKey, config),
+      creatorFeeX,
+      creatorFeeY,
+      tokenProgram,
+    }
+  }
+
+  const orderAccounts = (config: PublicKey, owner: PublicKey, seed: BN) => {
+    const [order] = PublicKey.findProgramAddressSync(
+      [Buffer.from("order"), config.toBuffer(), owner.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    return { order, orderVaultX: ata(mintX.publicKey, order), orderVaultY: ata(mintY.publicKey, order) }
+  }
+
+  // Initializes a pool owned by alice and seeds it with `liquidity` of each token
+  const createPool = async (seed: BN, creatorFeeBps: number) => {
+    const pool = poolAccounts(seed);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.initialize(seed, fee, alice.publicKey, creatorFeeBps).accountsPartial({ ...pool, initializer: alice.publicKey }).signers([alice]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.deposit(liquidity, liquidity, liquidity).accountsPartial({ ...pool, lpProvider: alice.publicKey }).signers([alice]).rpc()
+    )
+
+    return pool
+  }
+
+  const pool = poolAccounts(new BN(1));
+
+  it('should create mints', async () => {
+    let lamports = await getMinimumBalanceForRentExemptMint(program.provider.connection);
+    await airdrop(provider.connection, alice.publicKey);
+    await airdrop(provider.connection, bob.publicKey);
+
+    const transaction = new anchor.web3.Transaction();
+    transaction.instructions = [
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mintX.publicKey,
+        lamports,
+        space: MINT_SIZE,
+        programId: tokenProgram,
+      }),
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mintY.publicKey,
+        lamports,
+        space: MINT_SIZE,
+        programId: tokenProgram,
+      }),
+      createInitializeMint2Instruction(mintX.publicKey, 6, provider.publicKey, null, tokenProgram),
+      createInitializeMint2Instruction(mintY.publicKey, 6, provider.publicKey, null, tokenProgram),
+    ];
+    for (const user of [alice.publicKey, bob.publicKey]) {
+      for (const mint of [mintX.publicKey, mintY.publicKey]) {
+        transaction.instructions.push(
+          createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, ata(mint, user), user, mint, tokenProgram),
+          createMintToInstruction(mint, ata(mint, user), provider.publicKey, tokenAmount, undefined, tokenProgram),
+        );
+      }
+    }
+    await provider.sendAndConfirm(transaction, [mintX, mintY]);
+
+    assert.strictEqual((await balance(ata(mintY.publicKey, bob.publicKey))).toNumber(), tokenAmount)
+  })
+
+  it('should let alice create a pool and add liquidity', async () => {
+    await createPool(new BN(1), creatorFee);
+
+    const config = await program.account.config.fetch(pool.config);
+    assert.strictEqual(config.fee, fee)
+    assert.strictEqual(config.creatorFee, creatorFee)
+    assert.ok(config.creator.equals(alice.publicKey))
+    assert.ok((await balance(pool.vaultX)).eq(liquidity))
+    assert.ok((await balance(pool.vaultY)).eq(liquidity))
+  })
+
+  // Sells 100 X for at least 0.95 Y each. The first tokens would clear that price on
+  // the 1:1 pool, but selling all of them moves the curve to about 0.91.
+  const restingSeed = new BN(0);
+  const resting = orderAccounts(pool.config, bob.publicKey, restingSeed);
+  const restingAmount = new BN(100_000_000)
+  const restingPrice = new BN(950_000_000)
+
+  it('should rest a limit order the pool can\'t fill in full', async () => {
+    const before = await balance(ata(mintX.publicKey, bob.publicKey));
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.placeOrder(restingSeed, true, restingAmount, restingPrice).accountsPartial({ ...pool, ...resting, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+
+    try {
+      await program.methods.crankFill().accountsPartial({ ...pool, ...resting, cranker: provider.publicKey, oracle: null }).rpc()
+      assert.fail("an order is only ever filled for its whole amount")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "OrderNotCrossed")
+    }
+
+    const order = await program.account.order.fetch(resting.order);
+    assert.ok(order.amount.eq(restingAmount))
+    assert.ok(order.claimable.isZero())
+    assert.ok((await balance(resting.orderVaultX)).eq(restingAmount))
+    assert.ok(before.sub(await balance(ata(mintX.publicKey, bob.publicKey))).eq(restingAmount))
+  })
+
+  it('should fill resting orders a swap crosses', async () => {
+    const remainingAccounts = [resting.order, resting.orderVaultX, resting.orderVaultY].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+
+    // buying X with 300 Y lifts its price well past the order's
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.swap(false, new BN(300_000_000), new BN(1))
+        .accountsPartial({ ...pool, user: alice.publicKey, oracle: null })
+        .remainingAccounts(remainingAccounts)
+        .signers([alice])
+        .rpc()
+    )
+
+    const order = await program.account.order.fetch(resting.order);
+    assert.ok(order.amount.isZero())
+    assert.ok(order.claimable.gte(restingAmount.mul(restingPrice).div(PRICE_SCALE)))
+    assert.ok((await balance(resting.orderVaultX)).isZero())
+    assert.ok((await balance(resting.orderVaultY)).eq(order.claimable))
+  })
+
+  it('should pay an order\'s proceeds out to its owner', async () => {
+    const { claimable } = await program.account.order.fetch(resting.order);
+    const before = await balance(ata(mintY.publicKey, bob.publicKey));
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.claimOrder().accountsPartial({ ...pool, ...resting, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+
+    assert.ok((await balance(ata(mintY.publicKey, bob.publicKey))).sub(before).eq(claimable))
+    assert.ok((await program.account.order.fetch(resting.order)).claimable.isZero())
+
+    try {
+      await program.methods.claimOrder().accountsPartial({ ...pool, ...resting, owner: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("proceeds can only be claimed once")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "NothingToClaim")
+    }
+  })
+
+  // Sells 10 Y for at least 0.5 X each, which the pool already pays after the swap above
+  const crankedSeed = new BN(1);
+  const cranked = orderAccounts(pool.config, bob.publicKey, crankedSeed);
+
+  it('should let anyone crank a crossed order', async () => {
+    const amount = new BN(10_000_000);
+    const price = new BN(500_000_000);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.placeOrder(crankedSeed, false, amount, price).accountsPartial({ ...pool, ...cranked, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.crankFill().accountsPartial({ ...pool, ...cranked, cranker: provider.publicKey, oracle: null }).rpc()
+    )
+
+    const order = await program.account.order.fetch(cranked.order);
+    assert.ok(order.amount.isZero())
+    assert.ok(order.claimable.gte(amount.mul(price).div(PRICE_SCALE)))
+    assert.ok((await balance(cranked.orderVaultX)).eq(order.claimable))
+  })
+
+  it('should return unfilled and unclaimed tokens when an order is cancelled', async () => {
+    // 10 X at 10 Y each is far above anything the pool pays
+    const seed = new BN(2);
+    const unfilled = orderAccounts(pool.config, bob.publicKey, seed);
+    const bobMintX = ata(mintX.publicKey, bob.publicKey);
+    const before = await balance(bobMintX);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.placeOrder(seed, true, new BN(10_000_000), new BN(10_000_000_000)).accountsPartial({ ...pool, ...unfilled, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.cancelOrder().accountsPartial({ ...pool, ...unfilled, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+
+    assert.ok((await balance(bobMintX)).eq(before))
+    assert.isNull(await program.account.order.fetchNullable(unfilled.order))
+    assert.isNull(await provider.connection.getAccountInfo(unfilled.orderVaultX))
+    assert.isNull(await provider.connection.getAccountInfo(unfilled.orderVaultY))
+
+    // the cranked order was filled but never claimed
+    const { claimable } = await program.account.order.fetch(cranked.order);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.cancelOrder().accountsPartial({ ...pool, ...cranked, owner: bob.publicKey }).signers([bob]).rpc()
+    )
+
+    assert.ok((await balance(bobMintX)).sub(before).eq(claimable))
+    assert.isNull(await program.account.order.fetchNullable(cranked.order))
+  })
+  // tests/fixtures/config-v0.json is loaded at genesis by Anchor.toml: a pool with seed 42
+  // written in the layout from before Config carried a version
+  const legacyAuthority = anchor.web3.Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync("tests/fixtures/legacy-authority.json", "utf8"))));
+  const [legacyConfig] = PublicKey.findProgramAddressSync([Buffer.from("config"), new BN(42).toArrayLike(Buffer, "le", 8)], program.programId);
+
+  it('should let the authority migrate a pool created before Config was versioned', async () => {
+    assert.isBelow((await provider.connection.getAccountInfo(legacyConfig)).data.length, program.account.config.size)
+    await airdrop(provider.connection, legacyAuthority.publicKey);
+
+    try {
+      await program.methods.migrateConfig().accountsPartial({ authority: bob.publicKey, config: legacyConfig }).signers([bob]).rpc()
+      assert.fail("only the pool authority may migrate its config")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidAuthority")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.migrateConfig().accountsPartial({ authority: legacyAuthority.publicKey, config: legacyConfig }).signers([legacyAuthority]).rpc()
+    )
+
+    const config = await program.account.config.fetch(legacyConfig);
+    assert.strictEqual(config.version, 3)
+    assert.strictEqual(config.seed.toNumber(), 42)
+    assert.strictEqual(config.fee, 30)
+    assert.ok(config.authority.equals(legacyAuthority.publicKey))
+    assert.ok(config.oracle.equals(PublicKey.default))
+    assert.strictEqual(config.creatorFee, 0)
+    assert.strictEqual((await provider.connection.getAccountInfo(legacyConfig)).data.length, program.account.config.size)
+
+    for (const [authority, config] of [[legacyAuthority, legacyConfig], [alice, pool.config]] as const) {
+      try {
+        await program.methods.migrateConfig().accountsPartial({ authority: authority.publicKey, config }).signers([authority]).rpc()
+        assert.fail("configs already on the current version can't be migrated again")
+      } catch (err) {
+        assert.strictEqual(err.error.errorCode.code, "AlreadyMigrated")
+      }
+    }
+  })
+  // tests/fixtures/pyth-price-update.json is a fully verified Pyth update pricing one X at
+  // one Y, published long before the validator starts
+  const feed = new PublicKey("BEiygtCiMYNmzadJ49Z4W2TyAhBHrhuDqdgtPJa2gX9m");
+  const guarded = poolAccounts(new BN(2));
+
+  it('should let only the pool authority point it at an oracle', async () => {
+    await createPool(new BN(2), 0);
+
+    try {
+      await program.methods.updateOracle(feed, 100, 0).accountsPartial({ authority: bob.publicKey, config: guarded.config }).signers([bob]).rpc()
+      assert.fail("only the pool authority may set its oracle")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidAuthority")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.updateOracle(feed, 100, 60).accountsPartial({ authority: alice.publicKey, config: guarded.config }).signers([alice]).rpc()
+    )
+
+    const config = await program.account.config.fetch(guarded.config);
+    assert.ok(config.oracle.equals(feed))
+    assert.strictEqual(config.maxDeviationBps, 100)
+    assert.strictEqual(config.maxOracleAge, 60)
+  })
+
+  it('should reject swaps that move the pool price out of the oracle band', async () => {
+    const swap = (amount: number, oracle: PublicKey | null) => program.methods.swap(true, new BN(amount), new BN(1))
+      .accountsPartial({ ...guarded, user: bob.publicKey, oracle })
+      .signers([bob])
+      .rpc()
+
+    try {
+      await swap(1_000_000, feed)
+      assert.fail("the fixture price is older than the pool's max oracle age")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "StaleOracle")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.updateOracle(feed, 100, 0).accountsPartial({ authority: alice.publicKey, config: guarded.config }).signers([alice]).rpc()
+    )
+
+    try {
+      await swap(1_000_000, null)
+      assert.fail("guarded pools need their oracle to swap")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidOracle")
+    }
+
+    // 1 X moves the price about 0.2%, inside the 1% band
+    await confirmTransaction(provider.connection, await swap(1_000_000, feed))
+    const vaultX = await balance(guarded.vaultX);
+
+    // 50 X moves it about 9%
+    try {
+      await swap(50_000_000, feed)
+      assert.fail("swaps past the deviation band should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "PriceDeviationExceeded")
+    }
+
+    assert.ok((await balance(guarded.vaultX)).eq(vaultX))
+  })
+  it('should cap the creator\'s share of the swap fee', async () => {
+    try {
+      await createPool(new BN(3), 5_001)
+      assert.fail("creator fees above MAX_CREATOR_FEE should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "CreatorFeeTooHigh")
+    }
+  })
+
+  it('should route the creator\'s share of each swap fee to the creator fee vaults', async () => {
+    // 10 X pays a 30 bps fee of 30_000, of which the creator takes 20%
+    const amount = 10_000_000
+    const [vaultX, creatorFeeX] = [await balance(pool.vaultX), await balance(pool.creatorFeeX)];
+
+    try {
+      await program.methods.swap(true, new BN(amount), new BN(1)).accountsPartial({ ...pool, user: bob.publicKey, creatorFeeX: null, creatorFeeY: null, oracle: null }).signers([bob]).rpc()
+      assert.fail("pools charging a creator fee need the creator fee vaults")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MissingCreatorFeeVault")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.swap(true, new BN(amount), new BN(1)).accountsPartial({ ...pool, user: bob.publicKey, oracle: null }).signers([bob]).rpc()
+    )
+
+    assert.strictEqual((await balance(pool.creatorFeeX)).sub(creatorFeeX).toNumber(), 6_000)
+    assert.strictEqual((await balance(pool.vaultX)).sub(vaultX).toNumber(), amount - 6_000)
+  })
+
+  it('should pay the creator fee vaults out to the pool creator', async () => {
+    const [creatorFeeX, creatorFeeY] = [await balance(pool.creatorFeeX), await balance(pool.creatorFeeY)];
+    const [aliceMintX, aliceMintY] = [ata(mintX.publicKey, alice.publicKey), ata(mintY.publicKey, alice.publicKey)];
+    const [beforeX, beforeY] = [await balance(aliceMintX), await balance(aliceMintY)];
+
+    try {
+      await program.methods.claimCreatorFees().accountsPartial({ ...pool, creator: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("only the pool creator may claim its fees")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConstraintHasOne")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.claimCreatorFees().accountsPartial({ ...pool, creator: alice.publicKey }).signers([alice]).rpc()
+    )
+
+    assert.ok((await balance(aliceMintX)).sub(beforeX).eq(creatorFeeX))
+    assert.ok((await balance(aliceMintY)).sub(beforeY).eq(creatorFeeY))
+    assert.ok((await balance(pool.creatorFeeX)).isZero())
+    assert.ok((await balance(pool.creatorFeeY)).isZero())
+
+    try {
+      await program.methods.claimCreatorFees().accountsPartial({ ...pool, creator: alice.publicKey }).signers([alice]).rpc()
+      assert.fail("claimed fees can't be claimed again")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "NothingToClaim")
+    }
+  })
 });
+
+async function airdrop(connection: any, address: any, amount = 1_000_000_000) {
+  await connection.confirmTransaction(await connection.requestAirdrop(address, amount), "confirmed");
+}
+
+async function confirmTransaction(connection: any, signature: string) {
+  const block = await connection.getLatestBlockhash();
+
+  await connection.confirmTransaction({
+    signature,
+    ...block,
+  })
+
+  return signature
+}
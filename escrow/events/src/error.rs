@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EventError {
+    #[error("invalid base64 in program data: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("event data too short for a discriminator")]
+    TooShort,
+    #[error("failed to deserialize event: {0}")]
+    Deserialize(#[from] std::io::Error),
+}
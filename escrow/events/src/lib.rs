@@ -0,0 +1,62 @@
+pub mod error;
+pub mod logs;
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use escrow::{
+    BundleMade, BundleRefunded, BundleTaken, OfferAmended, OfferMade, OfferRefunded, OfferSettled,
+    OfferTaken,
+};
+
+pub use error::*;
+pub use logs::*;
+
+#[derive(Clone, Debug)]
+pub enum EscrowEvent {
+    Made(OfferMade),
+    Taken(OfferTaken),
+    Refunded(OfferRefunded),
+    Amended(OfferAmended),
+    Settled(OfferSettled),
+    BundleMade(BundleMade),
+    BundleTaken(BundleTaken),
+    BundleRefunded(BundleRefunded),
+}
+
+impl EscrowEvent {
+    /// Decodes a discriminator-prefixed event, returning `None` for events of other types.
+    pub fn decode(data: &[u8]) -> Result<Option<EscrowEvent>, EventError> {
+        if data.len() < 8 {
+            return Err(EventError::TooShort);
+        }
+        let (discriminator, mut body) = data.split_at(8);
+
+        let event = match discriminator {
+            d if d == OfferMade::DISCRIMINATOR => {
+                EscrowEvent::Made(OfferMade::deserialize(&mut body)?)
+            }
+            d if d == OfferTaken::DISCRIMINATOR => {
+                EscrowEvent::Taken(OfferTaken::deserialize(&mut body)?)
+            }
+            d if d == OfferRefunded::DISCRIMINATOR => {
+                EscrowEvent::Refunded(OfferRefunded::deserialize(&mut body)?)
+            }
+            d if d == OfferAmended::DISCRIMINATOR => {
+                EscrowEvent::Amended(OfferAmended::deserialize(&mut body)?)
+            }
+            d if d == OfferSettled::DISCRIMINATOR => {
+                EscrowEvent::Settled(OfferSettled::deserialize(&mut body)?)
+            }
+            d if d == BundleMade::DISCRIMINATOR => {
+                EscrowEvent::BundleMade(BundleMade::deserialize(&mut body)?)
+            }
+            d if d == BundleTaken::DISCRIMINATOR => {
+                EscrowEvent::BundleTaken(BundleTaken::deserialize(&mut body)?)
+            }
+            d if d == BundleRefunded::DISCRIMINATOR => {
+                EscrowEvent::BundleRefunded(BundleRefunded::deserialize(&mut body)?)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+}
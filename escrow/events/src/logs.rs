@@ -0,0 +1,92 @@
+use anchor_lang::prelude::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{EscrowEvent, EventError};
+
+const INVOKE: &str = "Program ";
+const DATA: &str = "Program data: ";
+
+/// Decodes the escrow events in a transaction's log messages, in emission order.
+///
+/// Only `Program data:` lines logged while the escrow program is the innermost invocation
+/// are considered, so data from CPIs into other programs is skipped.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<EscrowEvent>, EventError> {
+    let program_id = escrow::ID.to_string();
+    let mut stack: Vec<String> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+
+        if let Some(data) = log.strip_prefix(DATA) {
+            if stack.last() == Some(&program_id) {
+                if let Some(event) = EscrowEvent::decode(&STANDARD.decode(data)?)? {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = log.strip_prefix(INVOKE) {
+            let mut words = rest.split_whitespace();
+            let (Some(program), Some(action)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if program.parse::<Pubkey>().is_err() {
+                continue;
+            }
+            match action {
+                "invoke" => stack.push(program.to_string()),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::{AnchorSerialize, Discriminator};
+    use escrow::OfferRefunded;
+
+    use super::*;
+
+    fn program_data(event: &OfferRefunded) -> String {
+        let mut data = OfferRefunded::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("{DATA}{}", STANDARD.encode(data))
+    }
+
+    #[test]
+    fn decodes_only_escrow_program_data() {
+        let event = OfferRefunded {
+            escrow: Pubkey::new_unique(),
+            maker: Pubkey::new_unique(),
+            caller: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            amount: 42,
+        };
+        let other = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", escrow::ID),
+            "Program log: Instruction: Refund".to_string(),
+            format!("Program {other} invoke [2]"),
+            program_data(&event),
+            format!("Program {other} success"),
+            program_data(&event),
+            format!("Program {} success", escrow::ID),
+        ];
+
+        let events = parse_logs(&logs).unwrap();
+
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            EscrowEvent::Refunded(refunded) => {
+                assert_eq!(refunded.escrow, event.escrow);
+                assert_eq!(refunded.amount, 42);
+            }
+            other => panic!("unexpected event {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,606 @@
+use anchor_lang::{
+    prelude::{AccountInfo, Pubkey},
+    system_program, AccountDeserialize, AccountSerialize, InstructionData, Space, ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{
+        self, get_associated_token_address_with_program_id,
+        spl_associated_token_account::instruction::create_associated_token_account,
+    },
+    metadata::mpl_token_metadata::{self, types::Key},
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::{self, TransferFee, TransferFeeConfig},
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        instruction::{initialize_mint2, initialize_non_transferable_mint, mint_to},
+        state::Mint,
+    },
+    token_interface::TokenAccount,
+};
+use escrow::{EscrowConfig, EscrowError, EscrowState};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+pub const FEE_BPS: u16 = 100;
+pub const DECIMALS: u8 = 6;
+pub const WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+// Anchor's entrypoint wants the accounts to live as long as their data, which the builtin
+// processor signature can't express
+fn process_instruction<'info>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts: &'info [AccountInfo<'info>] = Box::leak(Box::new(accounts.to_vec()));
+    escrow::entry(program_id, accounts, data)
+}
+
+// Token Metadata isn't loaded, the escrow only reads the accounts the harness writes for it
+fn process_metadata_instruction(_: &Pubkey, _: &[AccountInfo], _: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+pub fn config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &escrow::ID)
+}
+
+pub fn treasury_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"treasury"], &escrow::ID)
+}
+
+pub fn maker_profile_address(maker: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"maker", maker.as_ref()], &escrow::ID).0
+}
+
+pub fn escrow_address(maker: &Pubkey, seed: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"escrow", maker.as_ref(), &seed.to_le_bytes()],
+        &escrow::ID,
+    )
+    .0
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::Metadata::find_pda(mint).0
+}
+
+pub fn master_edition_address(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::MasterEdition::find_pda(mint).0
+}
+
+pub fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+// The custom error code the program returns for `error`
+pub fn error_code(error: EscrowError) -> u32 {
+    error.into()
+}
+
+// An escrow program with its fee config and treasury already in place, as
+// `initialize_config` would leave them, and funded maker and taker wallets
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub maker: Keypair,
+    pub taker: Keypair,
+    pub token_program: Pubkey,
+}
+
+// The mints and token accounts of one maker/taker pair
+pub struct Market {
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub maker_mint_a_ata: Pubkey,
+    pub maker_mint_b_ata: Pubkey,
+    pub taker_mint_a_ata: Pubkey,
+    pub taker_mint_b_ata: Pubkey,
+    pub treasury_mint_b_ata: Pubkey,
+}
+
+impl Harness {
+    pub async fn new(token_program: Pubkey) -> Self {
+        let mut program_test =
+            ProgramTest::new("escrow", escrow::ID, processor!(process_instruction));
+        program_test.add_program(
+            "mpl_token_metadata",
+            mpl_token_metadata::ID,
+            processor!(process_metadata_instruction),
+        );
+        let maker = Keypair::new();
+        let taker = Keypair::new();
+        let rent = Rent::default();
+
+        let (config, bump) = config_address();
+        let (treasury, treasury_bump) = treasury_address();
+        let mut data = Vec::with_capacity(8 + EscrowConfig::INIT_SPACE);
+        EscrowConfig {
+            admin: Pubkey::new_unique(),
+            fee_bps: FEE_BPS,
+            fee_on_mint_a: false,
+            treasury_bump,
+            bump,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+
+        program_test.add_account(
+            config,
+            Account {
+                lamports: rent.minimum_balance(data.len()),
+                data,
+                owner: escrow::ID,
+                ..Account::default()
+            },
+        );
+        program_test.add_account(
+            treasury,
+            Account {
+                lamports: rent.minimum_balance(0),
+                owner: system_program::ID,
+                ..Account::default()
+            },
+        );
+        for wallet in [&maker, &taker] {
+            program_test.add_account(
+                wallet.pubkey(),
+                Account {
+                    lamports: WALLET_LAMPORTS,
+                    owner: system_program::ID,
+                    ..Account::default()
+                },
+            );
+        }
+
+        Self {
+            context: program_test.start_with_context().await,
+            maker,
+            taker,
+            token_program,
+        }
+    }
+
+    pub async fn create_mint(&mut self) -> Pubkey {
+        self.create_mint_from(Keypair::new(), &[], vec![]).await
+    }
+
+    pub async fn create_transfer_fee_mint(&mut self, fee_bps: u16) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.context.payer.pubkey();
+        let extension = transfer_fee::instruction::initialize_transfer_fee_config(
+            &self.token_program,
+            &mint.pubkey(),
+            Some(&payer),
+            Some(&payer),
+            fee_bps,
+            u64::MAX,
+        )
+        .unwrap();
+
+        self.create_mint_from(mint, &[ExtensionType::TransferFeeConfig], vec![extension])
+            .await
+    }
+
+    pub async fn create_non_transferable_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let extension =
+            initialize_non_transferable_mint(&self.token_program, &mint.pubkey()).unwrap();
+
+        self.create_mint_from(mint, &[ExtensionType::NonTransferable], vec![extension])
+            .await
+    }
+
+    // Creates a mint controlled by the test payer, running `extensions` before it is
+    // initialized
+    async fn create_mint_from(
+        &mut self,
+        mint: Keypair,
+        extension_types: &[ExtensionType],
+        extensions: Vec<Instruction>,
+    ) -> Pubkey {
+        let space = ExtensionType::try_calculate_account_len::<Mint>(extension_types).unwrap();
+        let lamports = self.rent().await.minimum_balance(space);
+        let payer = self.context.payer.pubkey();
+
+        let mut instructions = vec![system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            lamports,
+            space as u64,
+            &self.token_program,
+        )];
+        instructions.extend(extensions);
+        instructions.push(
+            initialize_mint2(&self.token_program, &mint.pubkey(), &payer, None, DECIMALS).unwrap(),
+        );
+
+        process(&mut self.context, &instructions, &[&mint])
+            .await
+            .unwrap();
+        mint.pubkey()
+    }
+
+    // Mints an NFT to `owner` whose metadata names `collection`, verified or not, with the
+    // metadata and master edition accounts written in Token Metadata's layout
+    pub async fn create_nft(
+        &mut self,
+        owner: &Pubkey,
+        collection: &Pubkey,
+        verified: bool,
+    ) -> Pubkey {
+        let mint = self.create_mint().await;
+        self.fund(owner, &mint, 1).await;
+
+        let mut metadata = vec![Key::MetadataV1 as u8];
+        metadata.extend_from_slice(self.context.payer.pubkey().as_ref()); // update authority
+        metadata.extend_from_slice(mint.as_ref());
+        // Name, symbol and uri
+        for field in ["NFT", "", ""] {
+            metadata.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            metadata.extend_from_slice(field.as_bytes());
+        }
+        // No seller fee or creators, unsold, mutable, no edition nonce or token standard
+        metadata.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0]);
+        metadata.extend_from_slice(&[1, verified as u8]); // Some(collection)
+        metadata.extend_from_slice(collection.as_ref());
+        // No uses, collection details or programmable config
+        metadata.extend_from_slice(&[0, 0, 0]);
+
+        let mut master_edition = vec![Key::MasterEditionV2 as u8];
+        master_edition.extend_from_slice(&1u64.to_le_bytes()); // supply
+        master_edition.extend_from_slice(&[1]);
+        master_edition.extend_from_slice(&0u64.to_le_bytes()); // max supply
+
+        self.set_metadata_account(&metadata_address(&mint), metadata);
+        self.set_metadata_account(&master_edition_address(&mint), master_edition);
+        mint
+    }
+
+    fn set_metadata_account(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: mpl_token_metadata::ID,
+            ..Account::default()
+        };
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    // Creates `owner`'s associated token account for `mint` and mints `amount` into it
+    pub async fn fund(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let payer = self.context.payer.pubkey();
+        let account = ata(owner, mint, &self.token_program);
+
+        let mut instructions = vec![create_associated_token_account(
+            &payer,
+            owner,
+            mint,
+            &self.token_program,
+        )];
+        if amount != 0 {
+            instructions
+                .push(mint_to(&self.token_program, mint, &account, &payer, &[], amount).unwrap());
+        }
+
+        process(&mut self.context, &instructions, &[])
+            .await
+            .unwrap();
+        account
+    }
+
+    // Two plain mints, with the maker holding mint A and the taker holding mint B
+    pub async fn market(&mut self, maker_amount: u64, taker_amount: u64) -> Market {
+        let mint_a = self.create_mint().await;
+        let mint_b = self.create_mint().await;
+        self.market_for(mint_a, mint_b, maker_amount, taker_amount)
+            .await
+    }
+
+    // Token accounts for both sides of a trade, created up front so takes don't pay rent
+    pub async fn market_for(
+        &mut self,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        maker_amount: u64,
+        taker_amount: u64,
+    ) -> Market {
+        let maker = self.maker.pubkey();
+        let taker = self.taker.pubkey();
+        let treasury = treasury_address().0;
+
+        Market {
+            mint_a,
+            mint_b,
+            maker_mint_a_ata: self.fund(&maker, &mint_a, maker_amount).await,
+            maker_mint_b_ata: self.fund(&maker, &mint_b, 0).await,
+            taker_mint_a_ata: self.fund(&taker, &mint_a, 0).await,
+            taker_mint_b_ata: self.fund(&taker, &mint_b, taker_amount).await,
+            treasury_mint_b_ata: self.fund(&treasury, &mint_b, 0).await,
+        }
+    }
+
+    pub fn make_ix(
+        &self,
+        market: &Market,
+        seed: u64,
+        receive_amount: u64,
+        deposit_amount: u64,
+    ) -> Instruction {
+        let maker = self.maker.pubkey();
+        let escrow = escrow_address(&maker, seed);
+
+        Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Make {
+                maker,
+                mint_a: market.mint_a,
+                mint_b: market.mint_b,
+                maker_mint_a_ata: Some(market.maker_mint_a_ata),
+                maker_profile: maker_profile_address(&maker),
+                escrow,
+                vault: Some(ata(&escrow, &market.mint_a, &self.token_program)),
+                system_program: system_program::ID,
+                associated_token_program: associated_token::ID,
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Make {
+                receive_amount,
+                deposit_amount,
+                expires_at: None,
+                allowed_taker: None,
+            }
+            .data(),
+        }
+    }
+
+    // Bids the deposit for any verified NFT of the collection passed as `market.mint_b`
+    pub fn make_collection_bid_ix(
+        &self,
+        market: &Market,
+        seed: u64,
+        deposit_amount: u64,
+    ) -> Instruction {
+        let mut ix = self.make_ix(market, seed, 1, deposit_amount);
+        ix.data = escrow::instruction::MakeCollectionBid {
+            deposit_amount,
+            expires_at: None,
+            allowed_taker: None,
+        }
+        .data();
+        ix
+    }
+
+    pub fn take_collection_bid_ix(&self, market: &Market, seed: u64, nft: &Pubkey) -> Instruction {
+        let maker = self.maker.pubkey();
+        let taker = self.taker.pubkey();
+        let escrow = escrow_address(&maker, seed);
+        let treasury = treasury_address().0;
+
+        Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::TakeCollectionBid {
+                taker,
+                maker,
+                mint_a: market.mint_a,
+                collection_mint: market.mint_b,
+                nft_mint: *nft,
+                taker_mint_a_ata: market.taker_mint_a_ata,
+                taker_nft_ata: ata(&taker, nft, &self.token_program),
+                maker_nft_ata: ata(&maker, nft, &self.token_program),
+                escrow,
+                vault: ata(&escrow, &market.mint_a, &self.token_program),
+                metadata: metadata_address(nft),
+                master_edition: master_edition_address(nft),
+                config: config_address().0,
+                treasury,
+                treasury_mint_a_ata: Some(ata(&treasury, &market.mint_a, &self.token_program)),
+                maker_profile: maker_profile_address(&maker),
+                metadata_program: mpl_token_metadata::ID,
+                system_program: system_program::ID,
+                associated_token_program: associated_token::ID,
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::TakeCollectionBid {}.data(),
+        }
+    }
+
+    pub fn take_ix(&self, market: &Market, seed: u64, max_pay: u64) -> Instruction {
+        self.take_for_ix(market, seed, max_pay, None)
+    }
+
+    // Takes the offer with mint A delivered to `recipient` instead of the taker
+    pub fn take_for_ix(
+        &self,
+        market: &Market,
+        seed: u64,
+        max_pay: u64,
+        recipient: Option<Pubkey>,
+    ) -> Instruction {
+        let maker = self.maker.pubkey();
+        let escrow = escrow_address(&maker, seed);
+
+        Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Take {
+                taker: self.taker.pubkey(),
+                maker,
+                mint_a: market.mint_a,
+                mint_b: market.mint_b,
+                taker_mint_a_ata: Some(market.taker_mint_a_ata),
+                recipient,
+                recipient_mint_a_ata: recipient
+                    .map(|recipient| ata(&recipient, &market.mint_a, &self.token_program)),
+                taker_mint_b_ata: Some(market.taker_mint_b_ata),
+                maker_mint_b_ata: Some(market.maker_mint_b_ata),
+                escrow,
+                vault: Some(ata(&escrow, &market.mint_a, &self.token_program)),
+                config: config_address().0,
+                treasury: treasury_address().0,
+                treasury_mint_a_ata: None,
+                treasury_mint_b_ata: Some(market.treasury_mint_b_ata),
+                maker_profile: maker_profile_address(&maker),
+                attestation: None,
+                system_program: system_program::ID,
+                associated_token_program: associated_token::ID,
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Take { max_pay }.data(),
+        }
+    }
+
+    // Takes every offer in `seeds` in one batch, each passed as its five remaining accounts
+    pub fn take_many_ix(&self, market: &Market, seeds: &[u64], max_spend: u64) -> Instruction {
+        let maker = self.maker.pubkey();
+        let mut accounts = escrow::accounts::TakeMany {
+            taker: self.taker.pubkey(),
+            mint_a: market.mint_a,
+            mint_b: market.mint_b,
+            taker_mint_a_ata: market.taker_mint_a_ata,
+            taker_mint_b_ata: market.taker_mint_b_ata,
+            config: config_address().0,
+            treasury: treasury_address().0,
+            treasury_mint_a_ata: None,
+            treasury_mint_b_ata: Some(market.treasury_mint_b_ata),
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+            token_program: self.token_program,
+        }
+        .to_account_metas(None);
+        for seed in seeds {
+            let escrow = escrow_address(&maker, *seed);
+            accounts.extend([
+                AccountMeta::new(escrow, false),
+                AccountMeta::new(ata(&escrow, &market.mint_a, &self.token_program), false),
+                AccountMeta::new(maker, false),
+                AccountMeta::new(market.maker_mint_b_ata, false),
+                AccountMeta::new(maker_profile_address(&maker), false),
+            ]);
+        }
+
+        Instruction {
+            program_id: escrow::ID,
+            accounts,
+            data: escrow::instruction::TakeMany { max_spend }.data(),
+        }
+    }
+
+    pub fn refund_ix(&self, market: &Market, seed: u64, caller: &Pubkey) -> Instruction {
+        let maker = self.maker.pubkey();
+        let escrow = escrow_address(&maker, seed);
+
+        Instruction {
+            program_id: escrow::ID,
+            accounts: escrow::accounts::Refund {
+                caller: *caller,
+                maker,
+                mint_a: market.mint_a,
+                mint_b: market.mint_b,
+                maker_mint_a_ata: Some(market.maker_mint_a_ata),
+                escrow,
+                vault: Some(ata(&escrow, &market.mint_a, &self.token_program)),
+                maker_profile: maker_profile_address(&maker),
+                system_program: system_program::ID,
+                associated_token_program: associated_token::ID,
+                token_program: self.token_program,
+            }
+            .to_account_metas(None),
+            data: escrow::instruction::Refund {}.data(),
+        }
+    }
+
+    pub async fn rent(&mut self) -> Rent {
+        self.context.banks_client.get_rent().await.unwrap()
+    }
+
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.account(address)
+            .await
+            .map_or(0, |account| account.lamports)
+    }
+
+    pub async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+        let account = self.account(address).await.expect("token account exists");
+        TokenAccount::try_deserialize(&mut &account.data[..])
+            .unwrap()
+            .amount
+    }
+
+    // The transfer fee a Token-2022 mint currently charges
+    pub async fn transfer_fee(&mut self, mint: &Pubkey) -> TransferFee {
+        let account = self.account(mint).await.expect("mint exists");
+        let mint = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+        mint.get_extension::<TransferFeeConfig>()
+            .unwrap()
+            .newer_transfer_fee
+    }
+
+    // Transfer fees harvested to a Token-2022 mint
+    pub async fn withheld_on_mint(&mut self, mint: &Pubkey) -> u64 {
+        let account = self.account(mint).await.expect("mint exists");
+        let mint = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+        mint.get_extension::<TransferFeeConfig>()
+            .unwrap()
+            .withheld_amount
+            .into()
+    }
+
+    pub async fn escrow_state(&mut self, seed: u64) -> Option<EscrowState> {
+        let address = escrow_address(&self.maker.pubkey(), seed);
+        let account = self.account(&address).await?;
+        Some(EscrowState::try_deserialize(&mut &account.data[..]).unwrap())
+    }
+}
+
+// Sends `instructions` with the test payer covering fees, so wallet balances only move by
+// what the program does
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+// Asserts the transaction failed in its first instruction with `error`
+pub fn assert_escrow_error(result: Result<(), BanksClientError>, error: EscrowError) {
+    assert_custom_error(result, error_code(error));
+}
+
+pub fn assert_custom_error(result: Result<(), BanksClientError>, code: u32) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(code))
+    );
+}
@@ -0,0 +1,377 @@
+use anchor_lang::AccountDeserialize;
+use anchor_spl::{
+    token::{self, spl_token::error::TokenError},
+    token_2022,
+};
+use escrow::{EscrowConfig, EscrowError, MakerProfile};
+use escrow_program_tests::*;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+const DEPOSIT: u64 = 100_000_000;
+const RECEIVE: u64 = 120_000_000;
+
+fn protocol_fee(amount: u64) -> u64 {
+    EscrowConfig {
+        admin: Default::default(),
+        fee_bps: FEE_BPS,
+        fee_on_mint_a: false,
+        treasury_bump: 0,
+        bump: 0,
+    }
+    .fee(amount)
+    .unwrap()
+}
+
+#[tokio::test]
+async fn take_settles_both_sides_and_closes_the_escrow() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let escrow = escrow_address(&h.maker.pubkey(), 0);
+    let vault = ata(&escrow, &market.mint_a, &token::ID);
+    assert_eq!(h.token_balance(&vault).await, DEPOSIT);
+    assert_eq!(h.token_balance(&market.maker_mint_a_ata).await, 0);
+
+    let rent = h.lamports(&escrow).await + h.lamports(&vault).await;
+    let taker_lamports = h.lamports(&h.taker.pubkey()).await;
+
+    let take = h.take_ix(&market, 0, RECEIVE);
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    let fee = protocol_fee(RECEIVE);
+    assert_eq!(h.token_balance(&market.taker_mint_a_ata).await, DEPOSIT);
+    assert_eq!(h.token_balance(&market.taker_mint_b_ata).await, 0);
+    assert_eq!(
+        h.token_balance(&market.maker_mint_b_ata).await,
+        RECEIVE - fee
+    );
+    assert_eq!(h.token_balance(&market.treasury_mint_b_ata).await, fee);
+
+    // Both rents go to the taker, who closed the offer
+    assert!(h.account(&escrow).await.is_none());
+    assert!(h.account(&vault).await.is_none());
+    assert_eq!(h.lamports(&h.taker.pubkey()).await, taker_lamports + rent);
+
+    let profile = h
+        .account(&maker_profile_address(&h.maker.pubkey()))
+        .await
+        .unwrap();
+    let profile = MakerProfile::try_deserialize(&mut &profile.data[..]).unwrap();
+    assert_eq!(
+        (
+            profile.offer_count,
+            profile.filled_count,
+            profile.refunded_count
+        ),
+        (1, 1, 0)
+    );
+}
+
+#[tokio::test]
+async fn take_for_a_recipient_delivers_the_deposit_to_them() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let recipient = Pubkey::new_unique();
+    let take = h.take_for_ix(&market, 0, RECEIVE, Some(recipient));
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    // The taker still pays, but the recipient's ATA is created and receives the vault
+    let recipient_mint_a_ata = ata(&recipient, &market.mint_a, &token::ID);
+    assert_eq!(h.token_balance(&recipient_mint_a_ata).await, DEPOSIT);
+    assert_eq!(h.token_balance(&market.taker_mint_a_ata).await, 0);
+    assert_eq!(h.token_balance(&market.taker_mint_b_ata).await, 0);
+    assert!(h.escrow_state(0).await.is_none());
+}
+
+#[tokio::test]
+async fn refund_returns_the_deposit_and_rent_to_the_maker() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let escrow = escrow_address(&h.maker.pubkey(), 0);
+    let vault = ata(&escrow, &market.mint_a, &token::ID);
+    let rent = h.lamports(&escrow).await + h.lamports(&vault).await;
+    let maker_lamports = h.lamports(&h.maker.pubkey()).await;
+
+    let refund = h.refund_ix(&market, 0, &h.maker.pubkey());
+    process(&mut h.context, &[refund], &[&h.maker])
+        .await
+        .unwrap();
+
+    assert_eq!(h.token_balance(&market.maker_mint_a_ata).await, DEPOSIT);
+    assert!(h.account(&escrow).await.is_none());
+    assert!(h.account(&vault).await.is_none());
+    assert_eq!(h.lamports(&h.maker.pubkey()).await, maker_lamports + rent);
+}
+
+#[tokio::test]
+async fn refund_by_another_signer_is_rejected_before_expiry() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let refund = h.refund_ix(&market, 0, &h.taker.pubkey());
+    let result = process(&mut h.context, &[refund], &[&h.taker]).await;
+
+    assert_escrow_error(result, EscrowError::RefundNotAllowed);
+    assert!(h.escrow_state(0).await.is_some());
+}
+
+#[tokio::test]
+async fn take_with_a_different_mint_b_is_rejected() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let (maker, taker) = (h.maker.pubkey(), h.taker.pubkey());
+    let other = h.create_mint().await;
+    let wrong = Market {
+        mint_b: other,
+        maker_mint_b_ata: h.fund(&maker, &other, 0).await,
+        taker_mint_b_ata: h.fund(&taker, &other, RECEIVE).await,
+        treasury_mint_b_ata: h.fund(&treasury_address().0, &other, 0).await,
+        ..market
+    };
+
+    let take = h.take_ix(&wrong, 0, RECEIVE);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+
+    assert_escrow_error(result, EscrowError::InvalidMintB);
+    assert_eq!(h.token_balance(&wrong.taker_mint_b_ata).await, RECEIVE);
+}
+
+#[tokio::test]
+async fn take_by_an_under_funded_taker_fails_and_leaves_the_offer_open() {
+    let mut h = Harness::new(token::ID).await;
+    let market = h.market(DEPOSIT, RECEIVE - 1).await;
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let take = h.take_ix(&market, 0, RECEIVE);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+
+    assert_custom_error(result, TokenError::InsufficientFunds as u32);
+    let vault = ata(
+        &escrow_address(&h.maker.pubkey(), 0),
+        &market.mint_a,
+        &token::ID,
+    );
+    assert_eq!(h.token_balance(&vault).await, DEPOSIT);
+    assert_eq!(h.token_balance(&market.taker_mint_b_ata).await, RECEIVE - 1);
+    assert_eq!(h.token_balance(&market.maker_mint_b_ata).await, 0);
+}
+
+#[tokio::test]
+async fn token_2022_transfer_fee_is_added_on_top_of_the_price() {
+    let mut h = Harness::new(token_2022::ID).await;
+    let mint_a = h.create_mint().await;
+    let mint_b = h.create_transfer_fee_mint(50).await;
+    let market = h.market_for(mint_a, mint_b, DEPOSIT, RECEIVE * 2).await;
+
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let fee = protocol_fee(RECEIVE);
+    let transfer_fee = h.transfer_fee(&mint_b).await;
+    let maker_transfer_fee = transfer_fee.calculate_inverse_fee(RECEIVE - fee).unwrap();
+    let treasury_transfer_fee = transfer_fee.calculate_fee(fee).unwrap();
+
+    // Slippage is checked on what leaves the taker, transfer fee included
+    let take = h.take_ix(&market, 0, RECEIVE + maker_transfer_fee - 1);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+    assert_escrow_error(result, EscrowError::SlippageExceeded);
+
+    let take = h.take_ix(&market, 0, RECEIVE + maker_transfer_fee);
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    // The maker receives the full price less the protocol fee, the taker covers the
+    // transfer fee on both the maker's share and the treasury's
+    assert_eq!(
+        h.token_balance(&market.maker_mint_b_ata).await,
+        RECEIVE - fee
+    );
+    assert_eq!(
+        h.token_balance(&market.treasury_mint_b_ata).await,
+        fee - treasury_transfer_fee
+    );
+    assert_eq!(
+        h.token_balance(&market.taker_mint_b_ata).await,
+        RECEIVE - maker_transfer_fee
+    );
+    assert_eq!(h.token_balance(&market.taker_mint_a_ata).await, DEPOSIT);
+
+    let escrow = escrow_address(&h.maker.pubkey(), 0);
+    assert!(h.account(&escrow).await.is_none());
+    assert!(h
+        .account(&ata(&escrow, &mint_a, &token_2022::ID))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn token_2022_take_many_counts_transfer_fees_against_the_max_spend() {
+    let mut h = Harness::new(token_2022::ID).await;
+    let mint_a = h.create_mint().await;
+    let mint_b = h.create_transfer_fee_mint(50).await;
+    let market = h.market_for(mint_a, mint_b, DEPOSIT, RECEIVE * 2).await;
+
+    // One offer only, the builtin processor can't CPI after an account was closed in the
+    // same instruction the way a second offer in the batch would
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let fee = protocol_fee(RECEIVE);
+    let transfer_fee = h.transfer_fee(&mint_b).await;
+    let outlay = RECEIVE + transfer_fee.calculate_inverse_fee(RECEIVE - fee).unwrap();
+
+    let take = h.take_many_ix(&market, &[0], outlay - 1);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+    assert_escrow_error(result, EscrowError::SlippageExceeded);
+
+    let take = h.take_many_ix(&market, &[0], outlay);
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    assert_eq!(
+        h.token_balance(&market.taker_mint_b_ata).await,
+        RECEIVE * 2 - outlay
+    );
+    assert_eq!(
+        h.token_balance(&market.maker_mint_b_ata).await,
+        RECEIVE - fee
+    );
+    assert_eq!(h.token_balance(&market.taker_mint_a_ata).await, DEPOSIT);
+    assert!(h.escrow_state(0).await.is_none());
+}
+
+#[tokio::test]
+async fn token_2022_fees_withheld_on_the_vault_are_harvested_before_it_closes() {
+    let mut h = Harness::new(token_2022::ID).await;
+    let mint_a = h.create_transfer_fee_mint(50).await;
+    let mint_b = h.create_mint().await;
+    let market = h.market_for(mint_a, mint_b, DEPOSIT, RECEIVE).await;
+    let escrow = escrow_address(&h.maker.pubkey(), 0);
+    let vault = ata(&escrow, &mint_a, &token_2022::ID);
+
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+    // The deposit's transfer fee stays withheld on the vault
+    let transfer_fee = h.transfer_fee(&mint_a).await;
+    let deposited = DEPOSIT - transfer_fee.calculate_fee(DEPOSIT).unwrap();
+    assert_eq!(h.token_balance(&vault).await, deposited);
+
+    let take = h.take_ix(&market, 0, RECEIVE);
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    assert!(h.account(&vault).await.is_none());
+    assert!(h.account(&escrow).await.is_none());
+    assert_eq!(h.withheld_on_mint(&mint_a).await, DEPOSIT - deposited);
+    assert_eq!(
+        h.token_balance(&market.taker_mint_a_ata).await,
+        deposited - transfer_fee.calculate_fee(deposited).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn token_2022_non_transferable_mint_is_rejected() {
+    let mut h = Harness::new(token_2022::ID).await;
+    let mint_a = h.create_non_transferable_mint().await;
+    let mint_b = h.create_mint().await;
+    let market = h.market_for(mint_a, mint_b, DEPOSIT, RECEIVE).await;
+
+    let make = h.make_ix(&market, 0, RECEIVE, DEPOSIT);
+    let result = process(&mut h.context, &[make], &[&h.maker]).await;
+
+    assert_escrow_error(result, EscrowError::UnsupportedMint);
+    assert!(h.escrow_state(0).await.is_none());
+    assert_eq!(h.token_balance(&market.maker_mint_a_ata).await, DEPOSIT);
+}
+
+#[tokio::test]
+async fn collection_bid_is_filled_by_a_verified_member_nft() {
+    let mut h = Harness::new(token::ID).await;
+    let mint_a = h.create_mint().await;
+    let collection = h.create_mint().await;
+    let market = h.market_for(mint_a, collection, DEPOSIT, 0).await;
+    let make = h.make_collection_bid_ix(&market, 0, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let nft = h.create_nft(&h.taker.pubkey(), &collection, true).await;
+    let take = h.take_collection_bid_ix(&market, 0, &nft);
+    process(&mut h.context, &[take], &[&h.taker]).await.unwrap();
+
+    // The NFT can't be split, so the protocol fee comes out of the deposit
+    let fee = protocol_fee(DEPOSIT);
+    let treasury_mint_a_ata = ata(&treasury_address().0, &mint_a, &token::ID);
+    assert_eq!(
+        h.token_balance(&market.taker_mint_a_ata).await,
+        DEPOSIT - fee
+    );
+    assert_eq!(h.token_balance(&treasury_mint_a_ata).await, fee);
+    assert_eq!(
+        h.token_balance(&ata(&h.maker.pubkey(), &nft, &token::ID))
+            .await,
+        1
+    );
+    assert_eq!(
+        h.token_balance(&ata(&h.taker.pubkey(), &nft, &token::ID))
+            .await,
+        0
+    );
+
+    let escrow = escrow_address(&h.maker.pubkey(), 0);
+    assert!(h.account(&escrow).await.is_none());
+    assert!(h
+        .account(&ata(&escrow, &mint_a, &token::ID))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn collection_bid_rejects_an_unverified_member_nft() {
+    let mut h = Harness::new(token::ID).await;
+    let mint_a = h.create_mint().await;
+    let collection = h.create_mint().await;
+    let market = h.market_for(mint_a, collection, DEPOSIT, 0).await;
+    let make = h.make_collection_bid_ix(&market, 0, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let nft = h.create_nft(&h.taker.pubkey(), &collection, false).await;
+    let take = h.take_collection_bid_ix(&market, 0, &nft);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+
+    assert_escrow_error(result, EscrowError::InvalidCollection);
+    assert!(h.escrow_state(0).await.is_some());
+    assert_eq!(h.token_balance(&market.taker_mint_a_ata).await, 0);
+}
+
+#[tokio::test]
+async fn collection_bid_rejects_an_nft_of_another_collection() {
+    let mut h = Harness::new(token::ID).await;
+    let mint_a = h.create_mint().await;
+    let collection = h.create_mint().await;
+    let market = h.market_for(mint_a, collection, DEPOSIT, 0).await;
+    let make = h.make_collection_bid_ix(&market, 0, DEPOSIT);
+    process(&mut h.context, &[make], &[&h.maker]).await.unwrap();
+
+    let other = h.create_mint().await;
+    let nft = h.create_nft(&h.taker.pubkey(), &other, true).await;
+    let take = h.take_collection_bid_ix(&market, 0, &nft);
+    let result = process(&mut h.context, &[take], &[&h.taker]).await;
+
+    assert_escrow_error(result, EscrowError::InvalidCollection);
+    assert!(h.escrow_state(0).await.is_some());
+    assert_eq!(
+        h.token_balance(&ata(&h.taker.pubkey(), &nft, &token::ID))
+            .await,
+        1
+    );
+}
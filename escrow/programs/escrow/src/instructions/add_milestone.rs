@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct AddMilestone<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    mut,
+    has_one = escrow,
+    seeds = [b"milestones", escrow.key().as_ref()],
+    bump = milestones.bump,
+    realloc = MilestonePlan::space(milestones.milestones.len() + 1),
+    realloc::payer = maker,
+    realloc::zero = false,
+  )]
+    pub milestones: Account<'info, MilestonePlan>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> AddMilestone<'info> {
+    pub fn add(&mut self, name: String, amount: u64) -> Result<()> {
+        require!(
+            self.escrow
+                .arbitration
+                .as_ref()
+                .is_some_and(|arbitration| !arbitration.disputed),
+            EscrowError::AlreadyDisputed
+        );
+        require!(
+            self.milestones.milestones.len() < MAX_MILESTONES,
+            EscrowError::TooManyMilestones
+        );
+        require!(
+            amount != 0 && name.len() <= MAX_MILESTONE_NAME_LEN,
+            EscrowError::InvalidMilestone
+        );
+
+        // Every pending milestone must stay covered by the vault
+        let pending = self
+            .milestones
+            .pending_amount()
+            .and_then(|pending| pending.checked_add(amount))
+            .ok_or(EscrowError::Overflow)?;
+        require!(pending <= self.vault.amount, EscrowError::InvalidMilestone);
+
+        self.milestones.milestones.push(Milestone {
+            name,
+            amount,
+            approved: false,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::transfer_checked;
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct Amend<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    // Omitted when offering native SOL
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = maker,
+    associated_token::token_program = token_program,
+  )]
+    pub maker_mint_a_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    mut,
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    has_one = mint_b @ EscrowError::InvalidMintB,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    // Omitted when offering native SOL
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Amend<'info> {
+    pub fn update_terms(
+        &mut self,
+        receive_amount: Option<u64>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require!(!self.escrow.is_arbitrated(), EscrowError::ArbitratedEscrow);
+        require!(!self.escrow.is_auction(), EscrowError::AuctionNotSupported);
+
+        if let Some(receive_amount) = receive_amount {
+            require!(!self.escrow.collection_bid, EscrowError::CollectionBid);
+            require!(
+                receive_amount > self.escrow.filled_amount,
+                EscrowError::InvalidReceiveAmount
+            );
+            self.escrow.receive_amount = receive_amount;
+        }
+
+        // The expiry can only move later, never appear on an open-ended offer
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp
+                    && self
+                        .escrow
+                        .expires_at
+                        .is_some_and(|current| expires_at > current),
+                EscrowError::InvalidExpiry
+            );
+            self.escrow.expires_at = Some(expires_at);
+        }
+        Ok(())
+    }
+
+    // Native SOL is held by the escrow account on top of its rent
+    fn deposit_amount(&self) -> Result<u64> {
+        if self.escrow.offers_native() {
+            let rent = Rent::get()?.minimum_balance(self.escrow.to_account_info().data_len());
+            return Ok(self.escrow.get_lamports().saturating_sub(rent));
+        }
+
+        let Some(vault) = &self.vault else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+        Ok(vault.amount)
+    }
+
+    pub fn top_up(&mut self, amount: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        if self.escrow.offers_native() {
+            // Transfer more lamports to the escrow account
+            let cpi_program = self.system_program.to_account_info();
+            let cpi_account = Transfer {
+                from: self.maker.to_account_info(),
+                to: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+            return transfer(cpi_ctx, amount);
+        }
+
+        let (Some(maker_mint_a_ata), Some(vault)) = (&self.maker_mint_a_ata, &mut self.vault)
+        else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
+        // Transfer more token a to escrow
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: maker_mint_a_ata.to_account_info(),
+            to: vault.to_account_info(),
+            authority: self.maker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        vault.reload()?;
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, amount: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        require!(
+            amount < self.deposit_amount()?,
+            EscrowError::InvalidWithdrawAmount
+        );
+
+        if self.escrow.offers_native() {
+            self.escrow.sub_lamports(amount)?;
+            self.maker.add_lamports(amount)?;
+            return Ok(());
+        }
+
+        let (Some(maker_mint_a_ata), Some(vault)) = (&self.maker_mint_a_ata, &mut self.vault)
+        else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: vault.to_account_info(),
+            to: maker_mint_a_ata.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        vault.reload()?;
+        Ok(())
+    }
+
+    pub fn emit_amended(&self) -> Result<()> {
+        emit!(OfferAmended {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            mint_a: self.escrow.mint_a,
+            mint_b: self.escrow.mint_b,
+            deposit_amount: self.deposit_amount()?,
+            receive_amount: self.escrow.receive_amount,
+            filled_amount: self.escrow.filled_amount,
+            expires_at: self.escrow.expires_at,
+        });
+        Ok(())
+    }
+}
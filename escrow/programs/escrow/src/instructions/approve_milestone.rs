@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::transfer_checked;
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct ApproveMilestone<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    pub payee: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    init_if_needed,
+    payer = maker,
+    associated_token::mint = mint_a,
+    associated_token::authority = payee,
+    associated_token::token_program = token_program,
+  )]
+    pub payee_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    mut,
+    has_one = escrow,
+    seeds = [b"milestones", escrow.key().as_ref()],
+    bump = milestones.bump,
+  )]
+    pub milestones: Account<'info, MilestonePlan>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ApproveMilestone<'info> {
+    pub fn approve(&mut self, index: u8, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let arbitration = self
+            .escrow
+            .arbitration
+            .as_ref()
+            .ok_or(EscrowError::NotArbitrated)?;
+        require_keys_eq!(
+            arbitration.payee,
+            self.payee.key(),
+            EscrowError::InvalidPayee
+        );
+
+        let milestone = self
+            .milestones
+            .milestones
+            .get_mut(index as usize)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        require!(!milestone.approved, EscrowError::MilestoneApproved);
+        milestone.approved = true;
+        let amount = milestone.amount;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.vault.to_account_info(),
+            to: self.payee_mint_a_ata.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer the milestone's amount from vault to payee
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        Ok(())
+    }
+}
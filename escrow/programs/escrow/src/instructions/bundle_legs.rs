@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, Create},
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::instructions::token_extensions::{require_no_transfer_hook, require_supported_mint};
+use crate::states::*;
+
+// Helpers shared by the bundle instructions, which receive one group of leg accounts per
+// stored leg through `remaining_accounts`
+
+// Checks `mint` is the leg's mint under the bundle's token program and returns its decimals.
+// Leg accounts fill `remaining_accounts`, so transfer-hook mints can't be bundled
+pub fn leg_decimals(mint: &AccountInfo, leg: &BundleLeg, token_program: &Pubkey) -> Result<u8> {
+    require_keys_eq!(mint.key(), leg.mint, EscrowError::InvalidBundleAccounts);
+    require_keys_eq!(
+        *mint.owner,
+        *token_program,
+        EscrowError::InvalidBundleAccounts
+    );
+    require_supported_mint(mint)?;
+    require_no_transfer_hook(mint)?;
+
+    let mint = Mint::try_deserialize(&mut &mint.try_borrow_data()?[..])?;
+    Ok(mint.decimals)
+}
+
+// Checks `account` is a token account of `mint` owned by `owner`
+pub fn require_token_account(account: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> Result<()> {
+    let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    require_keys_eq!(
+        token_account.owner,
+        *owner,
+        EscrowError::InvalidBundleAccounts
+    );
+    require_keys_eq!(
+        token_account.mint,
+        *mint,
+        EscrowError::InvalidBundleAccounts
+    );
+    Ok(())
+}
+
+// What a leg vault really holds. A transfer fee can leave it short of the leg amount and
+// anyone can send it more, and it only closes once empty
+pub fn vault_amount(vault: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount)
+}
+
+// Checks `account` is the associated token account of `authority` for `mint`
+pub fn require_ata(
+    account: &AccountInfo,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(
+        account.key(),
+        get_associated_token_address_with_program_id(authority, mint, token_program),
+        EscrowError::InvalidBundleAccounts
+    );
+    Ok(())
+}
+
+pub fn create_ata<'info>(
+    payer: AccountInfo<'info>,
+    associated_token: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    associated_token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_account = Create {
+        payer,
+        associated_token,
+        authority,
+        mint,
+        system_program,
+        token_program,
+    };
+    let cpi_ctx = CpiContext::new(associated_token_program, cpi_account);
+
+    create_idempotent(cpi_ctx)
+}
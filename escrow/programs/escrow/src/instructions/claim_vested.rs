@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+      init_if_needed,
+      payer = beneficiary,
+      associated_token::mint = mint_a,
+      associated_token::authority = beneficiary,
+      associated_token::token_program = token_program,
+  )]
+    pub beneficiary_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+      mut,
+      has_one = creator @ EscrowError::InvalidCreator,
+      has_one = beneficiary @ EscrowError::InvalidBeneficiary,
+      has_one = mint_a @ EscrowError::InvalidMintA,
+      seeds = [b"vesting", creator.key.as_ref(), vesting.seed.to_le_bytes().as_ref()],
+      bump = vesting.bump,
+  )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+      mut,
+      associated_token::mint = mint_a,
+      associated_token::authority = vesting,
+      associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimVested<'info> {
+    pub fn claim(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let amount = self
+            .vesting
+            .claimable_amount(Clock::get()?.unix_timestamp)
+            .ok_or(EscrowError::Overflow)?;
+        require!(amount != 0, EscrowError::NothingToClaim);
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.vault.to_account_info(),
+            to: self.beneficiary_mint_a_ata.to_account_info(),
+            authority: self.vesting.to_account_info(),
+        };
+
+        let creator_binding = self.vesting.creator.to_bytes();
+        let seed_binding = self.vesting.seed.to_le_bytes();
+        let bump_binding = self.vesting.bump;
+
+        let seeds: [&[u8]; 4] = [b"vesting", &creator_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer the newly unlocked tokens to the beneficiary
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+
+        self.vesting.claimed_amount = self
+            .vesting
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(EscrowError::Overflow)?;
+        Ok(())
+    }
+
+    // Returns the vault and vesting rent to the creator once the whole grant is claimed
+    pub fn close_if_claimed(&mut self) -> Result<()> {
+        if self.vesting.claimed_amount != self.vesting.total_amount {
+            return Ok(());
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = CloseAccount {
+            authority: self.vesting.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.creator.to_account_info(),
+        };
+
+        let creator_binding = self.vesting.creator.to_bytes();
+        let seed_binding = self.vesting.seed.to_le_bytes();
+        let bump_binding = self.vesting.bump;
+
+        let seeds: [&[u8]; 4] = [b"vesting", &creator_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+
+        self.vesting.close(self.creator.to_account_info())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct CreateMilestones<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+    has_one = maker @ EscrowError::InvalidMaker,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+    init,
+    payer = maker,
+    space = MilestonePlan::space(0),
+    seeds = [b"milestones", escrow.key().as_ref()],
+    bump,
+  )]
+    pub milestones: Account<'info, MilestonePlan>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateMilestones<'info> {
+    pub fn init_plan(&mut self, deadline: i64, bumps: CreateMilestonesBumps) -> Result<()> {
+        require!(self.escrow.is_arbitrated(), EscrowError::NotArbitrated);
+        require!(
+            deadline > Clock::get()?.unix_timestamp,
+            EscrowError::InvalidExpiry
+        );
+
+        self.milestones.set_inner(MilestonePlan {
+            escrow: self.escrow.key(),
+            deadline,
+            bump: bumps.milestones,
+            milestones: Vec::new(),
+        });
+        Ok(())
+    }
+}
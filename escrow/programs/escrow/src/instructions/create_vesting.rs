@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{
+    gross_amount, require_supported_mint, transfer_checked,
+};
+use crate::states::*;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateVesting<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub beneficiary: SystemAccount<'info>,
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = creator,
+    associated_token::token_program = token_program,
+  )]
+    pub creator_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    init,
+    payer = creator,
+    space = 8 + Vesting::INIT_SPACE,
+    seeds = [b"vesting", creator.key.as_ref(), seed.to_le_bytes().as_ref()],
+    bump,
+  )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+    init,
+    payer = creator,
+    associated_token::mint = mint_a,
+    associated_token::authority = vesting,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CreateVesting<'info> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_vesting(
+        &mut self,
+        seed: u64,
+        amount: u64,
+        start_ts: i64,
+        cliff_ts: i64,
+        end_ts: i64,
+        revoke_authority: Option<Pubkey>,
+        bumps: CreateVestingBumps,
+    ) -> Result<()> {
+        require!(amount != 0, EscrowError::InvalidSchedule);
+        Vesting::validate_schedule(start_ts, cliff_ts, end_ts)?;
+        require_supported_mint(&self.mint_a.to_account_info())?;
+
+        self.vesting.set_inner(Vesting {
+            seed,
+            creator: self.creator.key(),
+            beneficiary: self.beneficiary.key(),
+            mint_a: self.mint_a.key(),
+            total_amount: amount,
+            claimed_amount: 0,
+            start_ts,
+            cliff_ts,
+            end_ts,
+            revoke_authority,
+            bump: bumps.vesting,
+        });
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        // Lock the whole grant in the vault, covering any token transfer fee
+        let amount = gross_amount(&self.mint_a.to_account_info(), self.vesting.total_amount)?;
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint_a.to_account_info(),
+            from: self.creator_mint_a_ata.to_account_info(),
+            to: self.vault.to_account_info(),
+            authority: self.creator.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::program::Escrow;
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // Must be the program's upgrade authority
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+    init,
+    payer = admin,
+    space = 8 + EscrowConfig::INIT_SPACE,
+    seeds = [b"config"],
+    bump,
+  )]
+    pub config: Account<'info, EscrowConfig>,
+    #[account(
+    mut,
+    seeds = [b"treasury"],
+    bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Escrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::InvalidAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeConfig<'info> {
+    pub fn init_config(
+        &mut self,
+        fee_bps: u16,
+        fee_on_mint_a: bool,
+        bumps: InitializeConfigBumps,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.set_inner(EscrowConfig {
+            admin: self.admin.key(),
+            fee_bps,
+            fee_on_mint_a,
+            treasury_bump: bumps.treasury,
+            bump: bumps.config,
+        });
+        Ok(())
+    }
+
+    // Native SOL fees are paid straight into the treasury, so it must already be rent exempt
+    pub fn fund_treasury(&mut self) -> Result<()> {
+        let rent = Rent::get()?.minimum_balance(0);
+        let lamports = self.treasury.lamports();
+        if lamports >= rent {
+            return Ok(());
+        }
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_account = Transfer {
+            from: self.admin.to_account_info(),
+            to: self.treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+        transfer(cpi_ctx, rent - lamports)
+    }
+}
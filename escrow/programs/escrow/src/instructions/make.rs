@@ -1,40 +1,52 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer_checked, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+use crate::instructions::token_extensions::{require_supported_mint, transfer_checked};
 use crate::states::*;
 
 #[derive(Accounts)]
-#[instruction(seed:u64)]
 pub struct Make<'info> {
     #[account(mut)]
     pub maker: Signer<'info>,
     pub mint_a: InterfaceAccount<'info, Mint>,
     pub mint_b: InterfaceAccount<'info, Mint>,
+    // Omitted when offering native SOL
     #[account(
     mut,
     associated_token::mint = mint_a,
     associated_token::authority = maker,
+    associated_token::token_program = token_program,
   )]
-    pub maker_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    pub maker_mint_a_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    init_if_needed,
+    payer = maker,
+    space = 8 + MakerProfile::INIT_SPACE,
+    seeds = [b"maker", maker.key.as_ref()],
+    bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
     #[account(
     init,
     payer = maker,
     space = 8 + EscrowState::INIT_SPACE,
-    seeds = [b"escrow", maker.key.as_ref(), seed.to_le_bytes().as_ref()],
+    seeds = [b"escrow", maker.key.as_ref(), maker_profile.offer_count.to_le_bytes().as_ref()],
     bump,
   )]
     pub escrow: Account<'info, EscrowState>,
+    // Omitted when offering native SOL
     #[account(
     init,
     payer = maker,
     associated_token::mint = mint_a,
     associated_token::authority = escrow,
+    associated_token::token_program = token_program,
   )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -43,10 +55,28 @@ pub struct Make<'info> {
 impl<'info> Make<'info> {
     pub fn init_escrow_state(
         &mut self,
-        seed: u64,
         receive_amount: u64,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
         bumps: MakeBumps,
     ) -> Result<()> {
+        if let Some(expires_at) = expires_at {
+            require!(
+                expires_at > Clock::get()?.unix_timestamp,
+                EscrowError::InvalidExpiry
+            );
+        }
+        require_supported_mint(&self.mint_a.to_account_info())?;
+        require_supported_mint(&self.mint_b.to_account_info())?;
+
+        // A fresh profile reads as all zeroes, so only its owner and bump need setting
+        if self.maker_profile.maker == Pubkey::default() {
+            self.maker_profile.maker = self.maker.key();
+            self.maker_profile.bump = bumps.maker_profile;
+        }
+        let seed = self.maker_profile.offer_count;
+        self.maker_profile.offer_count = seed.checked_add(1).ok_or(EscrowError::Overflow)?;
+
         self.escrow.set_inner(EscrowState {
             seed,
             receive_amount,
@@ -54,20 +84,105 @@ impl<'info> Make<'info> {
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
+            expires_at,
+            filled_amount: 0,
+            allowed_taker,
+            arbitration: None,
+            auction: None,
+            condition: None,
+            collection_bid: false,
+        });
+        Ok(())
+    }
+
+    pub fn emit_made(&self, deposit_amount: u64) -> Result<()> {
+        emit!(OfferMade {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            seed: self.escrow.seed,
+            mint_a: self.escrow.mint_a,
+            mint_b: self.escrow.mint_b,
+            deposit_amount,
+            receive_amount: self.escrow.receive_amount,
+            expires_at: self.escrow.expires_at,
+            allowed_taker: self.escrow.allowed_taker,
+            auction: self.escrow.auction.clone(),
+            arbitration: self.escrow.arbitration.clone(),
+            condition: self.escrow.condition.clone(),
+            collection_bid: self.escrow.collection_bid,
+        });
+        Ok(())
+    }
+
+    // Prices the offer with a Dutch auction instead of the fixed receive amount
+    pub fn init_auction(&mut self, auction: DutchAuction) -> Result<()> {
+        auction.validate()?;
+
+        self.escrow.receive_amount = auction.start_receive_amount;
+        self.escrow.auction = Some(auction);
+        Ok(())
+    }
+
+    // Only lets the offer be taken while `condition`'s attestation satisfies it
+    pub fn init_condition(&mut self, condition: Condition) -> Result<()> {
+        self.escrow.condition = Some(condition);
+        Ok(())
+    }
+
+    // Treats mint B as a collection, so any one verified NFT from it fills the offer
+    pub fn init_collection_bid(&mut self) -> Result<()> {
+        require!(
+            !self.escrow.offers_native() && !self.escrow.asks_native(),
+            EscrowError::NativeNotSupported
+        );
+
+        self.escrow.receive_amount = 1;
+        self.escrow.collection_bid = true;
+        Ok(())
+    }
+
+    // Turns the offer into a payment the maker releases to `payee`, or `arbiter` splits
+    pub fn init_arbitration(&mut self, arbiter: Pubkey, payee: Pubkey) -> Result<()> {
+        require!(
+            !self.escrow.offers_native(),
+            EscrowError::NativeNotSupported
+        );
+
+        self.escrow.arbitration = Some(Arbitration {
+            arbiter,
+            payee,
+            disputed: false,
         });
         Ok(())
     }
 
-    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+    pub fn deposit(&mut self, amount: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if self.escrow.offers_native() {
+            // Transfer lamports to the escrow account, on top of its rent
+            let cpi_program = self.system_program.to_account_info();
+            let cpi_account = Transfer {
+                from: self.maker.to_account_info(),
+                to: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+            return transfer(cpi_ctx, amount);
+        }
+
+        let (Some(maker_mint_a_ata), Some(vault)) = (&self.maker_mint_a_ata, &self.vault) else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
         // Transfer token a to escrow
         let cpi_program = self.token_program.to_account_info();
         let cpi_account = TransferChecked {
             mint: self.mint_a.to_account_info(),
-            from: self.maker_mint_a_ata.to_account_info(),
-            to: self.vault.to_account_info(),
+            from: maker_mint_a_ata.to_account_info(),
+            to: vault.to_account_info(),
             authority: self.maker.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
 
         transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
         Ok(())
@@ -0,0 +1,115 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{TokenInterface, TransferChecked},
+};
+
+use crate::instructions::bundle_legs::*;
+use crate::instructions::token_extensions::transfer_checked;
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct MakeBundle<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+    init_if_needed,
+    payer = maker,
+    space = 8 + MakerProfile::INIT_SPACE,
+    seeds = [b"maker", maker.key.as_ref()],
+    bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    // Shares the profile's counter with escrows, the prefix keeps their addresses apart
+    #[account(
+    init,
+    payer = maker,
+    space = 8 + BundleEscrow::INIT_SPACE,
+    seeds = [b"bundle", maker.key.as_ref(), maker_profile.offer_count.to_le_bytes().as_ref()],
+    bump,
+  )]
+    pub bundle: Account<'info, BundleEscrow>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> MakeBundle<'info> {
+    pub fn init_bundle(
+        &mut self,
+        offer: Vec<BundleLeg>,
+        ask: Vec<BundleLeg>,
+        bumps: MakeBundleBumps,
+    ) -> Result<()> {
+        BundleEscrow::validate_legs(&offer)?;
+        BundleEscrow::validate_legs(&ask)?;
+
+        // A fresh profile reads as all zeroes, so only its owner and bump need setting
+        if self.maker_profile.maker == Pubkey::default() {
+            self.maker_profile.maker = self.maker.key();
+            self.maker_profile.bump = bumps.maker_profile;
+        }
+        let seed = self.maker_profile.offer_count;
+        self.maker_profile.offer_count = seed.checked_add(1).ok_or(EscrowError::Overflow)?;
+
+        self.bundle.set_inner(BundleEscrow {
+            seed,
+            maker: self.maker.key(),
+            offer,
+            ask,
+            bump: bumps.bundle,
+        });
+        Ok(())
+    }
+
+    pub fn emit_made(&self) -> Result<()> {
+        emit!(BundleMade {
+            bundle: self.bundle.key(),
+            maker: self.bundle.maker,
+            seed: self.bundle.seed,
+            offer: self.bundle.offer.clone(),
+            ask: self.bundle.ask.clone(),
+        });
+        Ok(())
+    }
+
+    // Expects [mint, maker_ata, vault] for every offer leg, in stored order
+    pub fn deposit(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let legs = self.bundle.offer.clone();
+        require!(
+            remaining_accounts.len() == legs.len() * 3,
+            EscrowError::InvalidBundleAccounts
+        );
+
+        for (leg, accounts) in legs.iter().zip(remaining_accounts.chunks(3)) {
+            let (mint, maker_ata, vault) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let decimals = leg_decimals(mint, leg, self.token_program.key)?;
+            require_token_account(maker_ata, self.maker.key, &leg.mint)?;
+            require_ata(vault, &self.bundle.key(), &leg.mint, self.token_program.key)?;
+
+            create_ata(
+                self.maker.to_account_info(),
+                vault.clone(),
+                self.bundle.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            // Transfer the leg's tokens to its vault
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: mint.clone(),
+                from: maker_ata.clone(),
+                to: vault.clone(),
+                authority: self.maker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+            transfer_checked(cpi_ctx, leg.amount, decimals)?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,49 @@
+pub mod add_milestone;
+pub mod amend;
+pub mod approve_milestone;
+pub mod bundle_legs;
+pub mod claim_vested;
+pub mod create_milestones;
+pub mod create_vesting;
+pub mod initialize_config;
 pub mod make;
+pub mod make_bundle;
+pub mod raise_dispute;
 pub mod refund;
+pub mod refund_bundle;
+pub mod refund_milestones;
+pub mod release;
+pub mod resolve;
+pub mod revoke;
 pub mod take;
+pub mod take_bundle;
+pub mod take_collection_bid;
+pub mod take_many;
+pub mod take_partial;
+pub mod token_extensions;
+pub mod update_config;
+pub mod withdraw_treasury;
 
+pub use add_milestone::*;
+pub use amend::*;
+pub use approve_milestone::*;
+pub use claim_vested::*;
+pub use create_milestones::*;
+pub use create_vesting::*;
+pub use initialize_config::*;
 pub use make::*;
+pub use make_bundle::*;
+pub use raise_dispute::*;
 pub use refund::*;
+pub use refund_bundle::*;
+pub use refund_milestones::*;
+pub use release::*;
+pub use resolve::*;
+pub use revoke::*;
 pub use take::*;
+pub use take_bundle::*;
+pub use take_collection_bid::*;
+pub use take_many::*;
+pub use take_partial::*;
+pub use update_config::*;
+pub use withdraw_treasury::*;
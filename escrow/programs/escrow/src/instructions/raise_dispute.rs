@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    // The maker or the payee
+    pub caller: Signer<'info>,
+    pub maker: SystemAccount<'info>,
+    #[account(
+    mut,
+    has_one = maker @ EscrowError::InvalidMaker,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+}
+
+impl<'info> RaiseDispute<'info> {
+    // Hands the vault to the arbiter, after which only `resolve` or `release` can settle it
+    pub fn raise_dispute(&mut self) -> Result<()> {
+        let caller = self.caller.key();
+        let maker = self.maker.key();
+        let arbitration = self
+            .escrow
+            .arbitration
+            .as_mut()
+            .ok_or(EscrowError::NotArbitrated)?;
+
+        require!(
+            caller == maker || caller == arbitration.payee,
+            EscrowError::DisputeNotAllowed
+        );
+        require!(!arbitration.disputed, EscrowError::AlreadyDisputed);
+
+        arbitration.disputed = true;
+        Ok(())
+    }
+}
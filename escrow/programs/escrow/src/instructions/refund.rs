@@ -1,63 +1,130 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{close_account, transfer_checked, CloseAccount, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
 use crate::states::*;
 
 #[derive(Accounts)]
 pub struct Refund<'info> {
+    // The maker, or anyone once the offer has expired
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
     #[account(mut)]
-    pub maker: Signer<'info>,
     pub mint_a: InterfaceAccount<'info, Mint>,
     pub mint_b: InterfaceAccount<'info, Mint>,
+    // Omitted when the offer is native SOL
     #[account(
-    mut,
+    init_if_needed,
+    payer = caller,
     associated_token::mint = mint_a,
     associated_token::authority = maker,
+    associated_token::token_program = token_program,
   )]
-    pub maker_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    pub maker_mint_a_ata: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(
     mut,
     close = maker,
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    has_one = mint_b @ EscrowError::InvalidMintB,
     seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
     bump = escrow.bump,
   )]
     pub escrow: Account<'info, EscrowState>,
+    // Omitted when the offer is native SOL
     #[account(
     mut,
     associated_token::mint = mint_a,
     associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    mut,
+    seeds = [b"maker", maker.key.as_ref()],
+    bump = maker_profile.bump,
   )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub maker_profile: Account<'info, MakerProfile>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> Refund<'info> {
-    pub fn withdraw(&mut self) -> Result<()> {
+    pub fn withdraw(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.escrow.is_arbitrated(), EscrowError::ArbitratedEscrow);
+        require!(
+            self.caller.key() == self.maker.key()
+                || self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::RefundNotAllowed
+        );
+
+        // Native SOL sits in the escrow account and goes back to the maker when it closes
+        if self.escrow.offers_native() {
+            let rent = Rent::get()?.minimum_balance(self.escrow.to_account_info().data_len());
+            return self.emit_refunded(self.escrow.get_lamports().saturating_sub(rent));
+        }
+
+        let (Some(vault), Some(maker_mint_a_ata)) = (&self.vault, &self.maker_mint_a_ata) else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
         let cpi_program = self.token_program.to_account_info();
         let cpi_account = TransferChecked {
             mint: self.mint_a.to_account_info(),
-            to: self.maker_mint_a_ata.to_account_info(),
-            from: self.vault.to_account_info(),
+            to: maker_mint_a_ata.to_account_info(),
+            from: vault.to_account_info(),
             authority: self.escrow.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_account);
 
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, vault.amount, self.mint_a.decimals)?;
+
+        self.emit_refunded(vault.amount)
+    }
+
+    fn emit_refunded(&self, amount: u64) -> Result<()> {
+        emit!(OfferRefunded {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            caller: self.caller.key(),
+            mint_a: self.escrow.mint_a,
+            mint_b: self.escrow.mint_b,
+            amount,
+        });
         Ok(())
     }
 
     pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.refunded_count = self
+            .maker_profile
+            .refunded_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        let Some(vault) = &self.vault else {
+            return Ok(());
+        };
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_account = CloseAccount {
             authority: self.escrow.to_account_info(),
-            account: self.vault.to_account_info(),
+            account: vault.to_account_info(),
             destination: self.maker.to_account_info(),
         };
 
@@ -67,9 +134,9 @@ impl<'info> Refund<'info> {
 
         let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
         let signer_seeds: &[&[&[u8]]] = &[&seeds];
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, &signer_seeds);
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
 
-        close_account(cpi_ctx)?;
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
 
         Ok(())
     }
@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::bundle_legs::*;
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct RefundBundle<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    #[account(
+      mut,
+      close = maker,
+      has_one = maker @ EscrowError::InvalidMaker,
+      seeds = [b"bundle", maker.key.as_ref(), bundle.seed.to_le_bytes().as_ref()],
+      bump = bundle.bump,
+  )]
+    pub bundle: Account<'info, BundleEscrow>,
+    #[account(
+      mut,
+      seeds = [b"maker", maker.key.as_ref()],
+      bump = maker_profile.bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RefundBundle<'info> {
+    // Expects [mint, vault, maker_ata] for every offer leg, in stored order. Transfer-fee mints
+    // must be writable so the fees withheld on their vault can be harvested
+    pub fn withdraw(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let legs = self.bundle.offer.clone();
+        require!(
+            remaining_accounts.len() == legs.len() * 3,
+            EscrowError::InvalidBundleAccounts
+        );
+
+        let maker_binding = self.bundle.maker.to_bytes();
+        let seed_binding = self.bundle.seed.to_le_bytes();
+        let bump_binding = self.bundle.bump;
+
+        let seeds: [&[u8]; 4] = [b"bundle", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        for (leg, accounts) in legs.iter().zip(remaining_accounts.chunks(3)) {
+            let (mint, vault, maker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let decimals = leg_decimals(mint, leg, self.token_program.key)?;
+            require_ata(vault, &self.bundle.key(), &leg.mint, self.token_program.key)?;
+            require_ata(maker_ata, self.maker.key, &leg.mint, self.token_program.key)?;
+
+            create_ata(
+                self.maker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            // Empty the leg's vault back to the maker
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: mint.clone(),
+                from: vault.clone(),
+                to: maker_ata.clone(),
+                authority: self.bundle.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            transfer_checked(cpi_ctx, vault_amount(vault)?, decimals)?;
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = CloseAccount {
+                authority: self.bundle.to_account_info(),
+                account: vault.clone(),
+                destination: self.maker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            close_vault(cpi_ctx, mint)?;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.refunded_count = self
+            .maker_profile
+            .refunded_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        emit!(BundleRefunded {
+            bundle: self.bundle.key(),
+            maker: self.bundle.maker,
+            offer: self.bundle.offer.clone(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct RefundMilestones<'info> {
+    // Anyone, once the deadline has passed or the milestones paid out the whole deposit
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    init_if_needed,
+    payer = caller,
+    associated_token::mint = mint_a,
+    associated_token::authority = maker,
+    associated_token::token_program = token_program,
+  )]
+    pub maker_mint_a_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    mut,
+    close = maker,
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+    mut,
+    close = maker,
+    has_one = escrow,
+    seeds = [b"milestones", escrow.key().as_ref()],
+    bump = milestones.bump,
+  )]
+    pub milestones: Account<'info, MilestonePlan>,
+    #[account(
+    mut,
+    seeds = [b"maker", maker.key.as_ref()],
+    bump = maker_profile.bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RefundMilestones<'info> {
+    pub fn withdraw(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let Some(arbitration) = self.escrow.arbitration.clone() else {
+            return err!(EscrowError::NotArbitrated);
+        };
+        require!(!arbitration.disputed, EscrowError::AlreadyDisputed);
+        let complete = self.milestones.is_complete(self.vault.amount);
+        require!(
+            complete || Clock::get()?.unix_timestamp >= self.milestones.deadline,
+            EscrowError::MilestonesNotDue
+        );
+
+        if self.vault.amount != 0 {
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: self.mint_a.to_account_info(),
+                from: self.vault.to_account_info(),
+                to: self.maker_mint_a_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+
+            let maker_binding = self.escrow.maker.to_bytes();
+            let seed_binding = self.escrow.seed.to_le_bytes();
+            let bump_binding = self.escrow.bump;
+
+            let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+            let signer_seeds: &[&[&[u8]]] = &[&seeds];
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            // Transfer the unapproved remainder back to the maker
+            transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        }
+
+        let profile = &mut self.maker_profile;
+        // Any remainder makes this a refund, even when every milestone was approved
+        match complete {
+            true => {
+                profile.filled_count = profile
+                    .filled_count
+                    .checked_add(1)
+                    .ok_or(EscrowError::Overflow)?
+            }
+            false => {
+                profile.refunded_count = profile
+                    .refunded_count
+                    .checked_add(1)
+                    .ok_or(EscrowError::Overflow)?
+            }
+        }
+
+        emit!(OfferSettled {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            payee: arbitration.payee,
+            mint_a: self.escrow.mint_a,
+            payee_amount: 0,
+            maker_amount: self.vault.amount,
+        });
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_account = CloseAccount {
+            authority: self.escrow.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+
+        Ok(())
+    }
+}
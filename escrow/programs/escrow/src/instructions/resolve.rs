@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct Resolve<'info> {
+    #[account(mut)]
+    pub arbiter: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    pub payee: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    init_if_needed,
+    payer = arbiter,
+    associated_token::mint = mint_a,
+    associated_token::authority = maker,
+    associated_token::token_program = token_program,
+  )]
+    pub maker_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    init_if_needed,
+    payer = arbiter,
+    associated_token::mint = mint_a,
+    associated_token::authority = payee,
+    associated_token::token_program = token_program,
+  )]
+    pub payee_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    mut,
+    close = maker,
+    has_one = maker @ EscrowError::InvalidMaker,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+    bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: the escrow's milestone plan, closed along with it if one was ever created
+    #[account(
+    mut,
+    seeds = [b"milestones", escrow.key().as_ref()],
+    bump,
+  )]
+    pub milestones: UncheckedAccount<'info>,
+    #[account(
+    mut,
+    seeds = [b"maker", maker.key.as_ref()],
+    bump = maker_profile.bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Resolve<'info> {
+    // Pays `payee_share_bps` of the vault to the payee and refunds the rest to the maker
+    pub fn withdraw(
+        &mut self,
+        payee_share_bps: u16,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let arbitration = self
+            .escrow
+            .arbitration
+            .as_ref()
+            .ok_or(EscrowError::NotArbitrated)?;
+        require_keys_eq!(
+            arbitration.arbiter,
+            self.arbiter.key(),
+            EscrowError::InvalidArbiter
+        );
+        require_keys_eq!(
+            arbitration.payee,
+            self.payee.key(),
+            EscrowError::InvalidPayee
+        );
+        require!(arbitration.disputed, EscrowError::NotDisputed);
+        require!(payee_share_bps <= 10_000, EscrowError::InvalidShare);
+
+        let payee_amount = (self.vault.amount as u128)
+            .checked_mul(payee_share_bps as u128)
+            .and_then(|amount| amount.checked_div(10_000))
+            .and_then(|amount| u64::try_from(amount).ok())
+            .ok_or(EscrowError::Overflow)?;
+        let maker_amount = self.vault.amount - payee_amount;
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        for (to, amount) in [
+            (self.payee_mint_a_ata.to_account_info(), payee_amount),
+            (self.maker_mint_a_ata.to_account_info(), maker_amount),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: self.mint_a.to_account_info(),
+                from: self.vault.to_account_info(),
+                to,
+                authority: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        }
+
+        emit!(OfferSettled {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            payee: self.payee.key(),
+            mint_a: self.escrow.mint_a,
+            payee_amount,
+            maker_amount,
+        });
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.filled_count = self
+            .maker_profile
+            .filled_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_account = CloseAccount {
+            authority: self.escrow.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.maker.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+
+        MilestonePlan::close_if_exists(
+            &self.milestones.to_account_info(),
+            &self.maker.to_account_info(),
+        )
+    }
+}
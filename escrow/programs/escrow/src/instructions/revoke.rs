@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct Revoke<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub creator: SystemAccount<'info>,
+    pub beneficiary: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+    init_if_needed,
+    payer = authority,
+    associated_token::mint = mint_a,
+    associated_token::authority = creator,
+    associated_token::token_program = token_program,
+  )]
+    pub creator_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    init_if_needed,
+    payer = authority,
+    associated_token::mint = mint_a,
+    associated_token::authority = beneficiary,
+    associated_token::token_program = token_program,
+  )]
+    pub beneficiary_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+    mut,
+    close = creator,
+    has_one = creator @ EscrowError::InvalidCreator,
+    has_one = beneficiary @ EscrowError::InvalidBeneficiary,
+    has_one = mint_a @ EscrowError::InvalidMintA,
+    seeds = [b"vesting", creator.key.as_ref(), vesting.seed.to_le_bytes().as_ref()],
+    bump = vesting.bump,
+  )]
+    pub vesting: Account<'info, Vesting>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = vesting,
+    associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> Revoke<'info> {
+    // Settles what has already vested with the beneficiary and returns the rest to the creator
+    pub fn withdraw(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.vesting.revoke_authority == Some(self.authority.key()),
+            EscrowError::RevokeNotAllowed
+        );
+
+        let vested = self
+            .vesting
+            .claimable_amount(Clock::get()?.unix_timestamp)
+            .ok_or(EscrowError::Overflow)?;
+        let unvested = self
+            .vault
+            .amount
+            .checked_sub(vested)
+            .ok_or(EscrowError::Overflow)?;
+
+        let creator_binding = self.vesting.creator.to_bytes();
+        let seed_binding = self.vesting.seed.to_le_bytes();
+        let bump_binding = self.vesting.bump;
+
+        let seeds: [&[u8]; 4] = [b"vesting", &creator_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        for (to, amount) in [
+            (self.beneficiary_mint_a_ata.to_account_info(), vested),
+            (self.creator_mint_a_ata.to_account_info(), unvested),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: self.mint_a.to_account_info(),
+                from: self.vault.to_account_info(),
+                to,
+                authority: self.vesting.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_account = CloseAccount {
+            authority: self.vesting.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.creator.to_account_info(),
+        };
+
+        let creator_binding = self.vesting.creator.to_bytes();
+        let seed_binding = self.vesting.seed.to_le_bytes();
+        let bump_binding = self.vesting.bump;
+
+        let seeds: [&[u8]; 4] = [b"vesting", &creator_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+
+        Ok(())
+    }
+}
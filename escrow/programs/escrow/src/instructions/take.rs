@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{close_account, transfer_checked, CloseAccount, TransferChecked},
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
 
+use crate::instructions::token_extensions::{close_vault, gross_amount, transfer_checked};
 use crate::states::*;
 
 #[derive(Accounts)]
@@ -14,66 +15,164 @@ pub struct Take<'info> {
     pub taker: Signer<'info>,
     #[account(mut)]
     pub maker: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
     pub mint_a: InterfaceAccount<'info, Mint>,
     pub mint_b: InterfaceAccount<'info, Mint>,
+    // Omitted when the offer is native SOL
     #[account(
       init_if_needed,
       payer=taker,
       associated_token::mint=mint_a,
       associated_token::authority=taker,
+      associated_token::token_program=token_program,
   )]
-    pub taker_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Receives the offer instead of the taker when set, e.g. for aggregators and smart wallets
+    #[account(mut)]
+    pub recipient: Option<SystemAccount<'info>>,
+    // Only needed with a recipient, when the offer isn't native SOL
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=recipient,
+      associated_token::token_program=token_program,
+  )]
+    pub recipient_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Omitted when the ask is native SOL
     #[account(
       mut,
       associated_token::mint=mint_b,
       associated_token::authority=taker,
+      associated_token::token_program=token_program,
   )]
-    pub taker_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub taker_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Omitted when the ask is native SOL
     #[account(
       init_if_needed,
       payer=taker,
       associated_token::mint=mint_b,
       associated_token::authority=maker,
+      associated_token::token_program=token_program,
   )]
-    pub maker_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub maker_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     #[account(
       mut,
       close=taker,
+      has_one = maker @ EscrowError::InvalidMaker,
+      has_one = mint_a @ EscrowError::InvalidMintA,
+      has_one = mint_b @ EscrowError::InvalidMintB,
       seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
       bump = escrow.bump,
   )]
     pub escrow: Account<'info, EscrowState>,
+    // Omitted when the offer is native SOL
     #[account(
       mut,
       associated_token::mint = mint_a,
       associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+  )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      seeds = [b"config"],
+      bump = config.bump,
+  )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    #[account(
+      mut,
+      seeds = [b"treasury"],
+      bump = config.treasury_bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    // Only needed when the config charges the fee in a token mint a
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Only needed when the config charges the fee in a token mint b
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_b,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+      mut,
+      seeds = [b"maker", maker.key.as_ref()],
+      bump = maker_profile.bump,
   )]
-    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub maker_profile: Account<'info, MakerProfile>,
+    /// CHECK: only needed for conditional offers, checked against the escrow's condition
+    pub attestation: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl<'info> Take<'info> {
-    pub fn withdraw(&mut self) -> Result<()> {
-        let cpi_program = self.token_program.to_account_info();
+    pub fn withdraw(&mut self, max_pay: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.escrow.is_arbitrated(), EscrowError::ArbitratedEscrow);
+        require!(!self.escrow.collection_bid, EscrowError::CollectionBid);
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.escrow.is_expired(now), EscrowError::OfferExpired);
+        require!(
+            self.escrow.is_allowed_taker(self.taker.key),
+            EscrowError::TakerNotAllowed
+        );
+        self.check_condition()?;
 
-        let cpi_account = TransferChecked {
-            from: self.taker_mint_b_ata.to_account_info(),
-            mint: self.mint_b.to_account_info(),
-            to: self.maker_mint_b_ata.to_account_info(),
-            authority: self.taker.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_account);
+        let amount = self
+            .escrow
+            .receive_amount_at(now)
+            .ok_or(EscrowError::Overflow)?;
+        let (to_maker, fee_b) = self.split_payment(amount)?;
+        // What actually leaves the taker, transfer fee included, is what slippage is checked on
+        let outlay = to_maker.checked_add(fee_b).ok_or(EscrowError::Overflow)?;
+        require!(outlay <= max_pay, EscrowError::SlippageExceeded);
 
-        // Transfer token b from taker to maker
-        transfer_checked(cpi_ctx, self.escrow.receive_amount, self.mint_b.decimals)?;
+        self.pay(to_maker, fee_b, hook_accounts)?;
 
-        let cpi_account = TransferChecked {
-            from: self.vault.to_account_info(),
-            mint: self.mint_a.to_account_info(),
-            to: self.taker_mint_a_ata.to_account_info(),
-            authority: self.escrow.to_account_info(),
+        // Native SOL sits in the escrow account and goes to the taker when it closes
+        if self.escrow.offers_native() {
+            let rent = Rent::get()?.minimum_balance(self.escrow.to_account_info().data_len());
+            let deposit = self.escrow.get_lamports().saturating_sub(rent);
+            let fee_a = match self.config.fee_on_mint_a {
+                true => self.config.fee(deposit).ok_or(EscrowError::Overflow)?,
+                false => 0,
+            };
+
+            self.escrow.sub_lamports(fee_a)?;
+            self.treasury.add_lamports(fee_a)?;
+            // Otherwise the deposit goes to the taker along with the rent
+            if let Some(recipient) = &self.recipient {
+                self.escrow.sub_lamports(deposit - fee_a)?;
+                recipient.add_lamports(deposit - fee_a)?;
+            }
+            return self.emit_taken(deposit, amount, fee_a + fee_b);
+        }
+
+        let Some(vault) = &self.vault else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+        let to = match &self.recipient {
+            Some(_) => self.recipient_mint_a_ata.as_ref(),
+            None => self.taker_mint_a_ata.as_ref(),
+        };
+        let Some(to) = to else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
+        let fee = match self.config.fee_on_mint_a {
+            true => self.config.fee(vault.amount).ok_or(EscrowError::Overflow)?,
+            false => 0,
         };
 
         let maker_binding = self.escrow.maker.to_bytes();
@@ -82,20 +181,166 @@ impl<'info> Take<'info> {
 
         let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
         let signer_seeds: &[&[&[u8]]] = &[&seeds];
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, &signer_seeds);
 
-        // Transfer token a from vault to taker
-        transfer_checked(cpi_ctx, self.vault.amount, self.mint_a.decimals)?;
+        if fee != 0 {
+            let Some(treasury_mint_a_ata) = &self.treasury_mint_a_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                from: vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: treasury_mint_a_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            // Transfer the protocol fee from vault to treasury
+            transfer_checked(cpi_ctx, fee, self.mint_a.decimals)?;
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            from: vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer the rest of token a from vault to taker, or their recipient
+        transfer_checked(cpi_ctx, vault.amount - fee, self.mint_a.decimals)?;
+
+        self.emit_taken(vault.amount, amount, fee + fee_b)
+    }
+
+    fn check_condition(&self) -> Result<()> {
+        let Some(condition) = &self.escrow.condition else {
+            return Ok(());
+        };
+        let Some(attestation) = &self.attestation else {
+            return err!(EscrowError::InvalidAttestation);
+        };
+
+        require!(
+            condition.is_met::<DiscriminatedValue>(attestation)?,
+            EscrowError::ConditionNotMet
+        );
+        Ok(())
+    }
+
+    fn emit_taken(&self, amount_a: u64, amount_b: u64, fee: u64) -> Result<()> {
+        emit!(OfferTaken {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            taker: self.taker.key(),
+            recipient: self
+                .recipient
+                .as_ref()
+                .map_or(self.taker.key(), |recipient| recipient.key()),
+            mint_a: self.escrow.mint_a,
+            mint_b: self.escrow.mint_b,
+            amount_a,
+            amount_b,
+            fee,
+            fee_on_mint_a: self.config.fee_on_mint_a,
+            filled: true,
+        });
+        Ok(())
+    }
+
+    // Splits the price of the ask into what is sent to the maker and the protocol fee. A
+    // token transfer fee is added on top of the maker's share so they receive it in full
+    fn split_payment(&self, amount: u64) -> Result<(u64, u64)> {
+        let fee = match self.config.fee_on_mint_a {
+            true => 0,
+            false => self.config.fee(amount).ok_or(EscrowError::Overflow)?,
+        };
+
+        let to_maker = match self.escrow.asks_native() {
+            true => amount - fee,
+            false => gross_amount(&self.mint_b.to_account_info(), amount - fee)?,
+        };
+        Ok((to_maker, fee))
+    }
+
+    // Transfer the maker's share of the price and the protocol fee from the taker
+    fn pay(&mut self, to_maker: u64, fee: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        if self.escrow.asks_native() {
+            self.pay_native(self.maker.to_account_info(), to_maker)?;
+            if fee != 0 {
+                self.pay_native(self.treasury.to_account_info(), fee)?;
+            }
+            return Ok(());
+        }
+
+        let Some(maker_mint_b_ata) = &self.maker_mint_b_ata else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+        self.pay_token(maker_mint_b_ata.to_account_info(), to_maker, hook_accounts)?;
 
+        if fee != 0 {
+            let Some(treasury_mint_b_ata) = &self.treasury_mint_b_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+            self.pay_token(treasury_mint_b_ata.to_account_info(), fee, hook_accounts)?;
+        }
         Ok(())
     }
 
+    fn pay_native(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_account = system_program::Transfer {
+            from: self.taker.to_account_info(),
+            to,
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+        system_program::transfer(cpi_ctx, amount)
+    }
+
+    fn pay_token(
+        &self,
+        to: AccountInfo<'info>,
+        amount: u64,
+        hook_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let Some(taker_mint_b_ata) = &self.taker_mint_b_ata else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            from: taker_mint_b_ata.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to,
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
+    }
+
     pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.filled_count = self
+            .maker_profile
+            .filled_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        let Some(vault) = &self.vault else {
+            return Ok(());
+        };
+
         let cpi_program = self.token_program.to_account_info();
 
         let cpi_account = CloseAccount {
             authority: self.escrow.to_account_info(),
-            account: self.vault.to_account_info(),
+            account: vault.to_account_info(),
             destination: self.taker.to_account_info(),
         };
 
@@ -105,9 +350,9 @@ impl<'info> Take<'info> {
 
         let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
         let signer_seeds: &[&[&[u8]]] = &[&seeds];
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, &signer_seeds);
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
 
-        close_account(cpi_ctx)?;
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
 
         Ok(())
     }
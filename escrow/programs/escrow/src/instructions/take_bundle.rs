@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::bundle_legs::*;
+use crate::instructions::token_extensions::{close_vault, gross_amount, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct TakeBundle<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    #[account(
+      mut,
+      close = taker,
+      has_one = maker @ EscrowError::InvalidMaker,
+      seeds = [b"bundle", maker.key.as_ref(), bundle.seed.to_le_bytes().as_ref()],
+      bump = bundle.bump,
+  )]
+    pub bundle: Account<'info, BundleEscrow>,
+    #[account(
+      mut,
+      seeds = [b"maker", maker.key.as_ref()],
+      bump = maker_profile.bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TakeBundle<'info> {
+    // Expects [mint, taker_ata, maker_ata] for every ask leg, in stored order, after the
+    // offer leg accounts
+    pub fn pay(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let offer_len = self.bundle.offer.len();
+        let legs = self.bundle.ask.clone();
+        require!(
+            remaining_accounts.len() == (offer_len + legs.len()) * 3,
+            EscrowError::InvalidBundleAccounts
+        );
+
+        let ask_accounts = &remaining_accounts[offer_len * 3..];
+        for (leg, accounts) in legs.iter().zip(ask_accounts.chunks(3)) {
+            let (mint, taker_ata, maker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let decimals = leg_decimals(mint, leg, self.token_program.key)?;
+            require_token_account(taker_ata, self.taker.key, &leg.mint)?;
+            require_ata(maker_ata, self.maker.key, &leg.mint, self.token_program.key)?;
+
+            create_ata(
+                self.taker.to_account_info(),
+                maker_ata.clone(),
+                self.maker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            // Transfer the leg's tokens from taker to maker, plus any token transfer fee
+            let amount = gross_amount(mint, leg.amount)?;
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: mint.clone(),
+                from: taker_ata.clone(),
+                to: maker_ata.clone(),
+                authority: self.taker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+            transfer_checked(cpi_ctx, amount, decimals)?;
+        }
+        Ok(())
+    }
+
+    // Expects [mint, vault, taker_ata] for every offer leg, in stored order. Transfer-fee mints
+    // must be writable so the fees withheld on their vault can be harvested
+    pub fn withdraw(&mut self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let legs = self.bundle.offer.clone();
+        let offer_accounts = &remaining_accounts[..legs.len() * 3];
+
+        let maker_binding = self.bundle.maker.to_bytes();
+        let seed_binding = self.bundle.seed.to_le_bytes();
+        let bump_binding = self.bundle.bump;
+
+        let seeds: [&[u8]; 4] = [b"bundle", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        for (leg, accounts) in legs.iter().zip(offer_accounts.chunks(3)) {
+            let (mint, vault, taker_ata) = (&accounts[0], &accounts[1], &accounts[2]);
+
+            let decimals = leg_decimals(mint, leg, self.token_program.key)?;
+            require_ata(vault, &self.bundle.key(), &leg.mint, self.token_program.key)?;
+            require_ata(taker_ata, self.taker.key, &leg.mint, self.token_program.key)?;
+
+            create_ata(
+                self.taker.to_account_info(),
+                taker_ata.clone(),
+                self.taker.to_account_info(),
+                mint.clone(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+
+            // Empty the leg's vault to the taker
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                mint: mint.clone(),
+                from: vault.clone(),
+                to: taker_ata.clone(),
+                authority: self.bundle.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            transfer_checked(cpi_ctx, vault_amount(vault)?, decimals)?;
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = CloseAccount {
+                authority: self.bundle.to_account_info(),
+                account: vault.clone(),
+                destination: self.taker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            close_vault(cpi_ctx, mint)?;
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.filled_count = self
+            .maker_profile
+            .filled_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        emit!(BundleTaken {
+            bundle: self.bundle.key(),
+            maker: self.bundle.maker,
+            taker: self.taker.key(),
+            offer: self.bundle.offer.clone(),
+            ask: self.bundle.ask.clone(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{MasterEditionAccount, Metadata, MetadataAccount},
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct TakeCollectionBid<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub collection_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      mut,
+      associated_token::mint=nft_mint,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=nft_mint,
+      associated_token::authority=maker,
+      associated_token::token_program=token_program,
+  )]
+    pub maker_nft_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      mut,
+      close=taker,
+      has_one = maker @ EscrowError::InvalidMaker,
+      has_one = mint_a @ EscrowError::InvalidMintA,
+      constraint = escrow.mint_b == collection_mint.key() @ EscrowError::InvalidMintB,
+      seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+      bump = escrow.bump,
+  )]
+    pub escrow: Box<Account<'info, EscrowState>>,
+    #[account(
+      mut,
+      associated_token::mint = mint_a,
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+  )]
+    pub vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    // The same metadata checks the marketplace's `List` performs
+    #[account(
+      seeds = [
+        b"metadata",
+        metadata_program.key().as_ref(),
+        nft_mint.key().as_ref(),
+      ],
+      seeds::program = metadata_program.key(),
+      constraint = metadata.collection.as_ref().is_some_and(|collection| {
+          collection.key == collection_mint.key() && collection.verified
+      }) @ EscrowError::InvalidCollection,
+      bump,
+  )]
+    pub metadata: Box<Account<'info, MetadataAccount>>,
+    #[account(
+      seeds = [
+        b"metadata",
+        metadata_program.key().as_ref(),
+        nft_mint.key().as_ref(),
+        b"edition",
+      ],
+      seeds::program = metadata_program.key(),
+      bump,
+  )]
+    pub master_edition: Box<Account<'info, MasterEditionAccount>>,
+    #[account(
+      seeds = [b"config"],
+      bump = config.bump,
+  )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    #[account(
+      seeds = [b"treasury"],
+      bump = config.treasury_bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    // Only needed when a protocol fee is configured
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+      mut,
+      seeds = [b"maker", maker.key.as_ref()],
+      bump = maker_profile.bump,
+  )]
+    pub maker_profile: Box<Account<'info, MakerProfile>>,
+    pub metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TakeCollectionBid<'info> {
+    // Transfer the NFT from taker to maker
+    pub fn pay(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(self.escrow.collection_bid, EscrowError::InvalidCollection);
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferExpired
+        );
+        require!(
+            self.escrow.is_allowed_taker(self.taker.key),
+            EscrowError::TakerNotAllowed
+        );
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            from: self.taker_nft_ata.to_account_info(),
+            mint: self.nft_mint.to_account_info(),
+            to: self.maker_nft_ata.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, 1, self.nft_mint.decimals)
+    }
+
+    // Empty the vault to the taker. An NFT can't be split, so the protocol fee always comes
+    // out of the deposit, whichever side the config charges
+    pub fn withdraw(&mut self, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let amount = self.vault.amount;
+        let fee = self.config.fee(amount).ok_or(EscrowError::Overflow)?;
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        if fee != 0 {
+            let Some(treasury_mint_a_ata) = &self.treasury_mint_a_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: treasury_mint_a_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            // Transfer the protocol fee from vault to treasury
+            transfer_checked(cpi_ctx, fee, self.mint_a.decimals)?;
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_mint_a_ata.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer the rest of token a from vault to taker
+        transfer_checked(cpi_ctx, amount - fee, self.mint_a.decimals)?;
+
+        emit!(OfferTaken {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            taker: self.taker.key(),
+            recipient: self.taker.key(),
+            mint_a: self.escrow.mint_a,
+            mint_b: self.nft_mint.key(),
+            amount_a: amount,
+            amount_b: 1,
+            fee,
+            fee_on_mint_a: true,
+            filled: true,
+        });
+        Ok(())
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        self.maker_profile.filled_count = self
+            .maker_profile
+            .filled_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = CloseAccount {
+            authority: self.escrow.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())
+    }
+}
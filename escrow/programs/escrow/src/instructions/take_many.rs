@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::bundle_legs::{create_ata, require_ata};
+use crate::instructions::token_extensions::{
+    close_vault, gross_amount, require_no_transfer_hook, transfer_checked,
+};
+use crate::states::*;
+
+// Accounts passed per offer in `remaining_accounts`
+const OFFER_ACCOUNTS: usize = 5;
+
+#[derive(Accounts)]
+pub struct TakeMany<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub mint_b: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      mut,
+      associated_token::mint=mint_b,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      seeds = [b"config"],
+      bump = config.bump,
+  )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    #[account(
+      seeds = [b"treasury"],
+      bump = config.treasury_bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    // Only needed when the config charges the fee in mint a
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Only needed when the config charges the fee in mint b
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_b,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TakeMany<'info> {
+    // Takes every offer passed as [escrow, vault, maker, maker_mint_b_ata, maker_profile],
+    // failing the whole batch if any offer can't be taken or the total exceeds `max_spend`
+    pub fn take_many(
+        &mut self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        max_spend: u64,
+    ) -> Result<()> {
+        let offers = remaining_accounts.chunks_exact(OFFER_ACCOUNTS);
+        require!(
+            offers.len() != 0 && offers.remainder().is_empty(),
+            EscrowError::InvalidOfferAccounts
+        );
+        // Offer accounts fill `remaining_accounts`, leaving no room for hook accounts
+        require_no_transfer_hook(&self.mint_a.to_account_info())?;
+        require_no_transfer_hook(&self.mint_b.to_account_info())?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut spent: u64 = 0;
+
+        for accounts in offers {
+            let (vault, maker, maker_mint_b_ata) = (&accounts[1], &accounts[2], &accounts[3]);
+            let escrow = Account::<EscrowState>::try_from(&accounts[0])?;
+            let mut maker_profile = Account::<MakerProfile>::try_from(&accounts[4])?;
+
+            require_keys_eq!(escrow.maker, maker.key(), EscrowError::InvalidMaker);
+            require_keys_eq!(escrow.mint_a, self.mint_a.key(), EscrowError::InvalidMintA);
+            require_keys_eq!(escrow.mint_b, self.mint_b.key(), EscrowError::InvalidMintB);
+            require_keys_eq!(maker_profile.maker, maker.key(), EscrowError::InvalidMaker);
+            require!(!escrow.is_arbitrated(), EscrowError::ArbitratedEscrow);
+            require!(!escrow.is_conditional(), EscrowError::ConditionalEscrow);
+            require!(!escrow.collection_bid, EscrowError::CollectionBid);
+            require!(
+                !escrow.offers_native() && !escrow.asks_native(),
+                EscrowError::NativeNotSupported
+            );
+            require!(!escrow.is_expired(now), EscrowError::OfferExpired);
+            require!(
+                escrow.is_allowed_taker(self.taker.key),
+                EscrowError::TakerNotAllowed
+            );
+            require!(
+                accounts[0].is_writable && accounts[4].is_writable,
+                EscrowError::InvalidOfferAccounts
+            );
+            require_ata(vault, &escrow.key(), &escrow.mint_a, self.token_program.key)?;
+            require_ata(
+                maker_mint_b_ata,
+                &escrow.maker,
+                &escrow.mint_b,
+                self.token_program.key,
+            )?;
+
+            let amount = escrow.receive_amount_at(now).ok_or(EscrowError::Overflow)?;
+            let (to_maker, fee_b) = self.split_payment(amount)?;
+            // Counts what actually leaves the taker, transfer fees included
+            spent = spent
+                .checked_add(to_maker)
+                .and_then(|spent| spent.checked_add(fee_b))
+                .ok_or(EscrowError::Overflow)?;
+            require!(spent <= max_spend, EscrowError::SlippageExceeded);
+
+            create_ata(
+                self.taker.to_account_info(),
+                maker_mint_b_ata.clone(),
+                maker.clone(),
+                self.mint_b.to_account_info(),
+                self.system_program.to_account_info(),
+                self.token_program.to_account_info(),
+                self.associated_token_program.to_account_info(),
+            )?;
+            self.pay(maker_mint_b_ata.clone(), to_maker, fee_b)?;
+            let (amount_a, fee_a) = self.withdraw(&escrow, vault.clone())?;
+
+            emit!(OfferTaken {
+                escrow: escrow.key(),
+                maker: escrow.maker,
+                taker: self.taker.key(),
+                recipient: self.taker.key(),
+                mint_a: escrow.mint_a,
+                mint_b: escrow.mint_b,
+                amount_a,
+                amount_b: amount,
+                fee: fee_a + fee_b,
+                fee_on_mint_a: self.config.fee_on_mint_a,
+                filled: true,
+            });
+
+            maker_profile.filled_count = maker_profile
+                .filled_count
+                .checked_add(1)
+                .ok_or(EscrowError::Overflow)?;
+            maker_profile.exit(&crate::ID)?;
+            escrow.close(self.taker.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    // Splits the offer's price into what is sent to the maker and the protocol fee. A token
+    // transfer fee is added on top of the maker's share so they receive it in full
+    fn split_payment(&self, amount: u64) -> Result<(u64, u64)> {
+        let fee = match self.config.fee_on_mint_a {
+            true => 0,
+            false => self.config.fee(amount).ok_or(EscrowError::Overflow)?,
+        };
+
+        let to_maker = gross_amount(&self.mint_b.to_account_info(), amount - fee)?;
+        Ok((to_maker, fee))
+    }
+
+    // Transfer the maker's share of the price and the protocol fee from the taker
+    fn pay(&self, maker_mint_b_ata: AccountInfo<'info>, to_maker: u64, fee: u64) -> Result<()> {
+        self.pay_token(maker_mint_b_ata, to_maker)?;
+        if fee != 0 {
+            let Some(treasury_mint_b_ata) = &self.treasury_mint_b_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+            self.pay_token(treasury_mint_b_ata.to_account_info(), fee)?;
+        }
+        Ok(())
+    }
+
+    fn pay_token(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            from: self.taker_mint_b_ata.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to,
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_account);
+
+        transfer_checked(cpi_ctx, amount, self.mint_b.decimals)
+    }
+
+    // Empties and closes the offer's vault into the taker, less the protocol fee, and
+    // returns the vault amount and fee
+    fn withdraw(
+        &self,
+        escrow: &Account<'info, EscrowState>,
+        vault: AccountInfo<'info>,
+    ) -> Result<(u64, u64)> {
+        let amount = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?.amount;
+        let fee = match self.config.fee_on_mint_a {
+            true => self.config.fee(amount).ok_or(EscrowError::Overflow)?,
+            false => 0,
+        };
+
+        let maker_binding = escrow.maker.to_bytes();
+        let seed_binding = escrow.seed.to_le_bytes();
+        let bump_binding = escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        let mut transfers = vec![(self.taker_mint_a_ata.to_account_info(), amount - fee)];
+        if fee != 0 {
+            let Some(treasury_mint_a_ata) = &self.treasury_mint_a_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+            transfers.push((treasury_mint_a_ata.to_account_info(), fee));
+        }
+
+        for (to, amount) in transfers {
+            let cpi_program = self.token_program.to_account_info();
+            let cpi_account = TransferChecked {
+                from: vault.clone(),
+                mint: self.mint_a.to_account_info(),
+                to,
+                authority: escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            transfer_checked(cpi_ctx, amount, self.mint_a.decimals)?;
+        }
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = CloseAccount {
+            authority: escrow.to_account_info(),
+            account: vault,
+            destination: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+        Ok((amount, fee))
+    }
+}
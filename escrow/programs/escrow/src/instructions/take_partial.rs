@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::{close_vault, gross_amount, transfer_checked};
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct TakePartial<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    #[account(mut)]
+    pub maker: SystemAccount<'info>,
+    // Writable so fees withheld on the vault can be harvested before it closes
+    #[account(mut)]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    pub mint_b: InterfaceAccount<'info, Mint>,
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_mint_a_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      mut,
+      associated_token::mint=mint_b,
+      associated_token::authority=taker,
+      associated_token::token_program=token_program,
+  )]
+    pub taker_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_b,
+      associated_token::authority=maker,
+      associated_token::token_program=token_program,
+  )]
+    pub maker_mint_b_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+      mut,
+      has_one = maker @ EscrowError::InvalidMaker,
+      has_one = mint_a @ EscrowError::InvalidMintA,
+      has_one = mint_b @ EscrowError::InvalidMintB,
+      seeds = [b"escrow", maker.key.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+      bump = escrow.bump,
+  )]
+    pub escrow: Account<'info, EscrowState>,
+    #[account(
+      mut,
+      associated_token::mint = mint_a,
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program,
+  )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+      seeds = [b"config"],
+      bump = config.bump,
+  )]
+    pub config: Box<Account<'info, EscrowConfig>>,
+    #[account(
+      seeds = [b"treasury"],
+      bump = config.treasury_bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    // Only needed when the config charges the fee in mint a
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_a,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_a_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    // Only needed when the config charges the fee in mint b
+    #[account(
+      init_if_needed,
+      payer=taker,
+      associated_token::mint=mint_b,
+      associated_token::authority=treasury,
+      associated_token::token_program=token_program,
+  )]
+    pub treasury_mint_b_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+      mut,
+      seeds = [b"maker", maker.key.as_ref()],
+      bump = maker_profile.bump,
+  )]
+    pub maker_profile: Account<'info, MakerProfile>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TakePartial<'info> {
+    pub fn withdraw(&mut self, amount_b: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        require!(!self.escrow.is_arbitrated(), EscrowError::ArbitratedEscrow);
+        require!(!self.escrow.is_auction(), EscrowError::AuctionNotSupported);
+        require!(
+            !self.escrow.is_conditional(),
+            EscrowError::ConditionalEscrow
+        );
+        require!(!self.escrow.collection_bid, EscrowError::CollectionBid);
+        require!(
+            !self.escrow.offers_native() && !self.escrow.asks_native(),
+            EscrowError::NativeNotSupported
+        );
+        require!(
+            !self.escrow.is_expired(Clock::get()?.unix_timestamp),
+            EscrowError::OfferExpired
+        );
+        require!(
+            self.escrow.is_allowed_taker(self.taker.key),
+            EscrowError::TakerNotAllowed
+        );
+        require!(
+            amount_b != 0 && amount_b <= self.escrow.remaining_receive_amount(),
+            EscrowError::InvalidFillAmount
+        );
+
+        let amount_a = self
+            .escrow
+            .fill_share(amount_b, self.vault.amount)
+            .ok_or(EscrowError::Overflow)?;
+        require!(amount_a != 0, EscrowError::FillTooSmall);
+
+        let (fee_a, fee_b) = match self.config.fee_on_mint_a {
+            true => (self.config.fee(amount_a).ok_or(EscrowError::Overflow)?, 0),
+            false => (0, self.config.fee(amount_b).ok_or(EscrowError::Overflow)?),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_account = TransferChecked {
+            from: self.taker_mint_b_ata.to_account_info(),
+            mint: self.mint_b.to_account_info(),
+            to: self.maker_mint_b_ata.to_account_info(),
+            authority: self.taker.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_account)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer part of token b from taker to maker, less the protocol fee, adding any
+        // token transfer fee so the maker receives the full amount
+        let gross_b = gross_amount(&self.mint_b.to_account_info(), amount_b - fee_b)?;
+        transfer_checked(cpi_ctx, gross_b, self.mint_b.decimals)?;
+
+        if fee_b != 0 {
+            let Some(treasury_mint_b_ata) = &self.treasury_mint_b_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+
+            let cpi_account = TransferChecked {
+                from: self.taker_mint_b_ata.to_account_info(),
+                mint: self.mint_b.to_account_info(),
+                to: treasury_mint_b_ata.to_account_info(),
+                authority: self.taker.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program.clone(), cpi_account)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            transfer_checked(cpi_ctx, fee_b, self.mint_b.decimals)?;
+        }
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        let cpi_account = TransferChecked {
+            from: self.vault.to_account_info(),
+            mint: self.mint_a.to_account_info(),
+            to: self.taker_mint_a_ata.to_account_info(),
+            authority: self.escrow.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        // Transfer the matching share of token a from vault to taker, less the protocol fee
+        transfer_checked(cpi_ctx, amount_a - fee_a, self.mint_a.decimals)?;
+
+        if fee_a != 0 {
+            let Some(treasury_mint_a_ata) = &self.treasury_mint_a_ata else {
+                return err!(EscrowError::MissingTokenAccount);
+            };
+
+            let cpi_account = TransferChecked {
+                from: self.vault.to_account_info(),
+                mint: self.mint_a.to_account_info(),
+                to: treasury_mint_a_ata.to_account_info(),
+                authority: self.escrow.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+                .with_remaining_accounts(hook_accounts.to_vec());
+
+            transfer_checked(cpi_ctx, fee_a, self.mint_a.decimals)?;
+        }
+
+        self.escrow.filled_amount = self
+            .escrow
+            .filled_amount
+            .checked_add(amount_b)
+            .ok_or(EscrowError::Overflow)?;
+
+        emit!(OfferTaken {
+            escrow: self.escrow.key(),
+            maker: self.escrow.maker,
+            taker: self.taker.key(),
+            recipient: self.taker.key(),
+            mint_a: self.escrow.mint_a,
+            mint_b: self.escrow.mint_b,
+            amount_a,
+            amount_b,
+            fee: fee_a + fee_b,
+            fee_on_mint_a: self.config.fee_on_mint_a,
+            filled: self.escrow.remaining_receive_amount() == 0,
+        });
+        Ok(())
+    }
+
+    // Closes the vault and escrow once the last of the receive amount has been paid
+    pub fn close_if_filled(&mut self) -> Result<()> {
+        if self.escrow.remaining_receive_amount() != 0 {
+            return Ok(());
+        }
+
+        self.maker_profile.filled_count = self
+            .maker_profile
+            .filled_count
+            .checked_add(1)
+            .ok_or(EscrowError::Overflow)?;
+
+        let cpi_program = self.token_program.to_account_info();
+
+        let cpi_account = CloseAccount {
+            authority: self.escrow.to_account_info(),
+            account: self.vault.to_account_info(),
+            destination: self.taker.to_account_info(),
+        };
+
+        let maker_binding = self.escrow.maker.to_bytes();
+        let seed_binding = self.escrow.seed.to_le_bytes();
+        let bump_binding = self.escrow.bump;
+
+        let seeds: [&[u8]; 4] = [b"escrow", &maker_binding, &seed_binding, &[bump_binding]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+        close_vault(cpi_ctx, &self.mint_a.to_account_info())?;
+
+        self.escrow.close(self.taker.to_account_info())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token_interface::{
+    self,
+    spl_token_2022::{
+        self,
+        extension::{
+            memo_transfer::memo_required,
+            non_transferable::NonTransferable,
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::{
+                instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig,
+            },
+            transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+        },
+        onchain,
+        state::{Account, Mint},
+    },
+    CloseAccount, TransferChecked,
+};
+
+use crate::states::*;
+
+// Helpers for Token-2022 mints, whose extensions change how escrow transfers behave
+
+// Rejects mints the escrow can't safely hold: non-transferable tokens could never leave the
+// vault, and a permanent delegate could drain it
+pub fn require_supported_mint(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    require!(
+        mint.get_extension::<NonTransferable>().is_err()
+            && mint.get_extension::<PermanentDelegate>().is_err(),
+        EscrowError::UnsupportedMint
+    );
+    Ok(())
+}
+
+// For instructions whose `remaining_accounts` are already spoken for
+pub fn require_no_transfer_hook(mint: &AccountInfo) -> Result<()> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    require!(
+        transfer_hook::get_program_id(&mint).is_none(),
+        EscrowError::TransferHookNotSupported
+    );
+    Ok(())
+}
+
+// Returns what has to be sent so that `amount` arrives after the mint's transfer fee
+pub fn gross_amount(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    if *mint.owner != spl_token_2022::ID || amount == 0 {
+        return Ok(amount);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(amount);
+    };
+
+    let fee = config
+        .calculate_inverse_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(EscrowError::Overflow)?;
+    Ok(amount.checked_add(fee).ok_or(EscrowError::Overflow)?)
+}
+
+// The escrow can't write the memo an account with MemoTransfer requires on incoming transfers
+fn require_no_memo(account: &AccountInfo) -> Result<()> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = account.try_borrow_data()?;
+    let account = StateWithExtensions::<Account>::unpack(&data)?;
+    require!(
+        !memo_required(&account),
+        EscrowError::MemoTransferNotSupported
+    );
+    Ok(())
+}
+
+// Transfer fees withheld on a token account, which keep Token-2022 from closing it
+fn withheld_amount(account: &AccountInfo) -> Result<u64> {
+    if *account.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = account.try_borrow_data()?;
+    let account = StateWithExtensions::<Account>::unpack(&data)?;
+    Ok(account
+        .get_extension::<TransferFeeAmount>()
+        .map_or(0, |fees| fees.withheld_amount.into()))
+}
+
+// `transfer_checked` that forwards the context's remaining accounts as the extra accounts of
+// a transfer-hook mint
+pub fn transfer_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    require_no_memo(&ctx.accounts.to)?;
+
+    if ctx.remaining_accounts.is_empty() {
+        return token_interface::transfer_checked(ctx, amount, decimals);
+    }
+
+    onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+// `close_account` for an emptied vault. Fees withheld on it are first harvested to `mint`,
+// which must then be writable
+pub fn close_vault<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CloseAccount<'info>>,
+    mint: &AccountInfo<'info>,
+) -> Result<()> {
+    if withheld_amount(&ctx.accounts.account)? != 0 {
+        let ix = harvest_withheld_tokens_to_mint(
+            ctx.program.key,
+            mint.key,
+            &[ctx.accounts.account.key],
+        )?;
+        invoke(&ix, &[mint.clone(), ctx.accounts.account.clone()])?;
+    }
+
+    token_interface::close_account(ctx)
+}
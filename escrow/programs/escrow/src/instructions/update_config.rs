@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+    mut,
+    has_one = admin @ EscrowError::InvalidAdmin,
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+    pub config: Account<'info, EscrowConfig>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn update_fee(&mut self, fee_bps: u16, fee_on_mint_a: bool) -> Result<()> {
+        require!(fee_bps <= MAX_FEE_BPS, EscrowError::FeeTooHigh);
+
+        self.config.fee_bps = fee_bps;
+        self.config.fee_on_mint_a = fee_on_mint_a;
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::instructions::token_extensions::transfer_checked;
+use crate::states::*;
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+    has_one = admin @ EscrowError::InvalidAdmin,
+    seeds = [b"config"],
+    bump = config.bump,
+  )]
+    pub config: Account<'info, EscrowConfig>,
+    #[account(
+    mut,
+    seeds = [b"treasury"],
+    bump = config.treasury_bump,
+  )]
+    pub treasury: SystemAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    // Omitted when withdrawing native SOL
+    #[account(
+    mut,
+    associated_token::mint = mint,
+    associated_token::authority = treasury,
+    associated_token::token_program = token_program,
+  )]
+    pub treasury_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+    // Omitted when withdrawing native SOL
+    #[account(
+    init_if_needed,
+    payer = admin,
+    associated_token::mint = mint,
+    associated_token::authority = admin,
+    associated_token::token_program = token_program,
+  )]
+    pub admin_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    pub fn withdraw(&mut self, amount: u64, hook_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let seeds: [&[u8]; 2] = [b"treasury", &[self.config.treasury_bump]];
+        let signer_seeds: &[&[&[u8]]] = &[&seeds];
+
+        if is_native_mint(&self.mint.key()) {
+            // The treasury keeps its rent so native fees can keep landing in it
+            let rent = Rent::get()?.minimum_balance(0);
+            require!(
+                self.treasury.lamports().saturating_sub(rent) >= amount,
+                EscrowError::InsufficientTreasury
+            );
+
+            let cpi_program = self.system_program.to_account_info();
+            let cpi_account = system_program::Transfer {
+                from: self.treasury.to_account_info(),
+                to: self.admin.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds);
+
+            return system_program::transfer(cpi_ctx, amount);
+        }
+
+        let (Some(treasury_ata), Some(admin_ata)) = (&self.treasury_ata, &self.admin_ata) else {
+            return err!(EscrowError::MissingTokenAccount);
+        };
+        require!(
+            treasury_ata.amount >= amount,
+            EscrowError::InsufficientTreasury
+        );
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_account = TransferChecked {
+            mint: self.mint.to_account_info(),
+            from: treasury_ata.to_account_info(),
+            to: admin_ata.to_account_info(),
+            authority: self.treasury.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_account, signer_seeds)
+            .with_remaining_accounts(hook_accounts.to_vec());
+
+        transfer_checked(cpi_ctx, amount, self.mint.decimals)
+    }
+}
@@ -12,26 +12,269 @@ declare_id!("7F83HDdhKh85GArEurYnZwdN9gLkNGDnkefwDiZphkjC");
 pub mod escrow {
     use super::*;
 
-    pub fn make(
-        ctx: Context<Make>,
-        seed: u64,
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,
+        fee_on_mint_a: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_config(fee_bps, fee_on_mint_a, ctx.bumps)?;
+        ctx.accounts.fund_treasury()?;
+        Ok(())
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: u16,
+        fee_on_mint_a: bool,
+    ) -> Result<()> {
+        ctx.accounts.update_fee(fee_bps, fee_on_mint_a)
+    }
+
+    pub fn withdraw_treasury<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawTreasury<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount, ctx.remaining_accounts)
+    }
+
+    pub fn make<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        receive_amount: u64,
+        deposit_amount: u64,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow_state(receive_amount, expires_at, allowed_taker, ctx.bumps)?;
+        ctx.accounts
+            .deposit(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_made(deposit_amount)?;
+        Ok(())
+    }
+
+    pub fn make_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        deposit_amount: u64,
+        auction: DutchAuction,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow_state(0, expires_at, allowed_taker, ctx.bumps)?;
+        ctx.accounts.init_auction(auction)?;
+        ctx.accounts
+            .deposit(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_made(deposit_amount)?;
+        Ok(())
+    }
+
+    // Like `make`, but the offer can only be taken while `condition`'s attestation satisfies it
+    pub fn make_conditional<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
         receive_amount: u64,
         deposit_amount: u64,
+        condition: Condition,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
     ) -> Result<()> {
         ctx.accounts
-            .init_escrow_state(seed, receive_amount, ctx.bumps)?;
-        ctx.accounts.deposit(deposit_amount)?;
+            .init_escrow_state(receive_amount, expires_at, allowed_taker, ctx.bumps)?;
+        ctx.accounts.init_condition(condition)?;
+        ctx.accounts
+            .deposit(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_made(deposit_amount)?;
         Ok(())
     }
 
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        ctx.accounts.withdraw()?;
+    // Bids `deposit_amount` of mint A for any one verified NFT of the `mint_b` collection
+    pub fn make_collection_bid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        deposit_amount: u64,
+        expires_at: Option<i64>,
+        allowed_taker: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts
+            .init_escrow_state(0, expires_at, allowed_taker, ctx.bumps)?;
+        ctx.accounts.init_collection_bid()?;
+        ctx.accounts
+            .deposit(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_made(deposit_amount)?;
+        Ok(())
+    }
+
+    // Funds a vault for `payee` that the maker releases, or `arbiter` splits after a dispute.
+    // `mint_b` is unused and can be passed as mint A.
+    pub fn make_arbitrated<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Make<'info>>,
+        deposit_amount: u64,
+        arbiter: Pubkey,
+        payee: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.init_escrow_state(0, None, None, ctx.bumps)?;
+        ctx.accounts.init_arbitration(arbiter, payee)?;
+        ctx.accounts
+            .deposit(deposit_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_made(deposit_amount)?;
+        Ok(())
+    }
+
+    pub fn release<'info>(ctx: Context<'_, '_, 'info, 'info, Release<'info>>) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
         ctx.accounts.close()?;
         Ok(())
     }
 
-    pub fn refund(ctx: Context<Refund>) -> Result<()> {
-        ctx.accounts.withdraw()?;
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+        ctx.accounts.raise_dispute()
+    }
+
+    pub fn resolve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Resolve<'info>>,
+        payee_share_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts
+            .withdraw(payee_share_bps, ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn create_milestones(ctx: Context<CreateMilestones>, deadline: i64) -> Result<()> {
+        ctx.accounts.init_plan(deadline, ctx.bumps)
+    }
+
+    pub fn add_milestone(ctx: Context<AddMilestone>, name: String, amount: u64) -> Result<()> {
+        ctx.accounts.add(name, amount)
+    }
+
+    pub fn approve_milestone<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ApproveMilestone<'info>>,
+        index: u8,
+    ) -> Result<()> {
+        ctx.accounts.approve(index, ctx.remaining_accounts)
+    }
+
+    pub fn refund_milestones<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundMilestones<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn amend<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Amend<'info>>,
+        receive_amount: Option<u64>,
+        top_up_amount: u64,
+        withdraw_amount: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.update_terms(receive_amount, expires_at)?;
+        ctx.accounts.top_up(top_up_amount, ctx.remaining_accounts)?;
+        ctx.accounts
+            .withdraw(withdraw_amount, ctx.remaining_accounts)?;
+        ctx.accounts.emit_amended()?;
+        Ok(())
+    }
+
+    pub fn take<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Take<'info>>,
+        max_pay: u64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(max_pay, ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn take_collection_bid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeCollectionBid<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.pay(ctx.remaining_accounts)?;
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn take_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakeMany<'info>>,
+        max_spend: u64,
+    ) -> Result<()> {
+        ctx.accounts.take_many(ctx.remaining_accounts, max_spend)
+    }
+
+    pub fn take_partial<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TakePartial<'info>>,
+        amount_b: u64,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(amount_b, ctx.remaining_accounts)?;
+        ctx.accounts.close_if_filled()?;
+        Ok(())
+    }
+
+    pub fn refund<'info>(ctx: Context<'_, '_, 'info, 'info, Refund<'info>>) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn make_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MakeBundle<'info>>,
+        offer: Vec<BundleLeg>,
+        ask: Vec<BundleLeg>,
+    ) -> Result<()> {
+        ctx.accounts.init_bundle(offer, ask, ctx.bumps)?;
+        ctx.accounts.deposit(ctx.remaining_accounts)?;
+        ctx.accounts.emit_made()?;
+        Ok(())
+    }
+
+    pub fn take_bundle<'info>(ctx: Context<'_, '_, 'info, 'info, TakeBundle<'info>>) -> Result<()> {
+        ctx.accounts.pay(ctx.remaining_accounts)?;
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn refund_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RefundBundle<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
+        ctx.accounts.close()?;
+        Ok(())
+    }
+
+    pub fn create_vesting<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateVesting<'info>>,
+        seed: u64,
+        amount: u64,
+        start_ts: i64,
+        cliff_ts: i64,
+        end_ts: i64,
+        revoke_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.init_vesting(
+            seed,
+            amount,
+            start_ts,
+            cliff_ts,
+            end_ts,
+            revoke_authority,
+            ctx.bumps,
+        )?;
+        ctx.accounts.deposit(ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn claim_vested<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimVested<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.claim(ctx.remaining_accounts)?;
+        ctx.accounts.close_if_claimed()?;
+        Ok(())
+    }
+
+    pub fn revoke<'info>(ctx: Context<'_, '_, 'info, 'info, Revoke<'info>>) -> Result<()> {
+        ctx.accounts.withdraw(ctx.remaining_accounts)?;
         ctx.accounts.close()?;
         Ok(())
     }
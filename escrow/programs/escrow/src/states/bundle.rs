@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::states::EscrowError;
+
+pub const MAX_BUNDLE_LEGS: usize = 4;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct BundleLeg {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BundleEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    #[max_len(MAX_BUNDLE_LEGS)]
+    pub offer: Vec<BundleLeg>, // Deposited by the maker, one vault per mint
+    #[max_len(MAX_BUNDLE_LEGS)]
+    pub ask: Vec<BundleLeg>, // Paid by the taker straight to the maker
+    pub bump: u8,
+}
+
+impl BundleEscrow {
+    pub fn validate_legs(legs: &[BundleLeg]) -> Result<()> {
+        require!(
+            !legs.is_empty() && legs.len() <= MAX_BUNDLE_LEGS,
+            EscrowError::InvalidBundleLegs
+        );
+        for (i, leg) in legs.iter().enumerate() {
+            require!(leg.amount != 0, EscrowError::InvalidBundleLegs);
+            require!(
+                legs[..i].iter().all(|other| other.mint != leg.mint),
+                EscrowError::InvalidBundleLegs
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+
+use crate::states::EscrowError;
+
+// Gates `take` on a value attested by an account owned by `oracle`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct Condition {
+    pub oracle: Pubkey,
+    pub attestation: Pubkey,
+    pub comparison: Comparison,
+    pub threshold: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+    Equal,
+}
+
+impl Condition {
+    // Checks the attestation account and whether its value satisfies the comparison
+    pub fn is_met<P: AttestationParser>(&self, attestation: &AccountInfo) -> Result<bool> {
+        require_keys_eq!(
+            attestation.key(),
+            self.attestation,
+            EscrowError::InvalidAttestation
+        );
+        require_keys_eq!(
+            *attestation.owner,
+            self.oracle,
+            EscrowError::InvalidAttestation
+        );
+
+        let value = P::value(&attestation.try_borrow_data()?)?;
+        Ok(self.comparison.holds(value, self.threshold))
+    }
+}
+
+impl Comparison {
+    pub fn holds(&self, value: i64, threshold: i64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+            Comparison::Equal => value == threshold,
+        }
+    }
+}
+
+// Reads the attested value out of an oracle account's data
+pub trait AttestationParser {
+    fn value(data: &[u8]) -> Result<i64>;
+}
+
+// Anchor-style attestation accounts: an 8-byte discriminator followed by the value as a
+// little-endian i64
+pub struct DiscriminatedValue;
+
+impl AttestationParser for DiscriminatedValue {
+    fn value(data: &[u8]) -> Result<i64> {
+        let bytes = data
+            .get(8..16)
+            .ok_or(EscrowError::InvalidAttestation)?
+            .try_into()
+            .map_err(|_| EscrowError::InvalidAttestation)?;
+        Ok(i64::from_le_bytes(bytes))
+    }
+}
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_FEE_BPS: u16 = 1_000; // Hard cap of 10% on the protocol fee
+
+#[account]
+#[derive(InitSpace)]
+pub struct EscrowConfig {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub fee_on_mint_a: bool, // Charge the fee on the offered mint instead of the payment
+    pub treasury_bump: u8,
+    pub bump: u8,
+}
+
+impl EscrowConfig {
+    // Protocol fee owed on `amount`, rounded down
+    pub fn fee(&self, amount: u64) -> Option<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.fee_bps as u128)?
+            .checked_div(10_000)?;
+        u64::try_from(fee).ok()
+    }
+}
@@ -0,0 +1,101 @@
+use anchor_lang::error_code;
+
+#[error_code]
+pub enum EscrowError {
+    #[msg("The offer has expired")]
+    OfferExpired,
+    #[msg("The expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Only the maker can refund before the offer expires")]
+    RefundNotAllowed,
+    #[msg("The fill amount must be between 1 and the remaining receive amount")]
+    InvalidFillAmount,
+    #[msg("The fill is too small to receive any of the deposit")]
+    FillTooSmall,
+    #[msg("This offer is reserved for another taker")]
+    TakerNotAllowed,
+    #[msg("The maker does not match the escrow")]
+    InvalidMaker,
+    #[msg("Mint A does not match the escrow")]
+    InvalidMintA,
+    #[msg("Mint B does not match the escrow")]
+    InvalidMintB,
+    #[msg("The receive amount must exceed what has already been filled")]
+    InvalidReceiveAmount,
+    #[msg("Cannot withdraw the whole deposit, refund the offer instead")]
+    InvalidWithdrawAmount,
+    #[msg("Bundles need 1 to 4 legs with distinct mints and non-zero amounts")]
+    InvalidBundleLegs,
+    #[msg("Bundle leg accounts do not match the stored legs")]
+    InvalidBundleAccounts,
+    #[msg("A token account required for this mint was not provided")]
+    MissingTokenAccount,
+    #[msg("Native SOL offers do not support this instruction")]
+    NativeNotSupported,
+    #[msg("Fee is above the allowed maximum")]
+    FeeTooHigh,
+    #[msg("Only the config admin can do this")]
+    InvalidAdmin,
+    #[msg("Treasury balance is too low for this withdrawal")]
+    InsufficientTreasury,
+    #[msg("Vesting needs start <= cliff <= end, a non-zero duration and amount")]
+    InvalidSchedule,
+    #[msg("Nothing has vested since the last claim")]
+    NothingToClaim,
+    #[msg("Only the revoke authority can revoke this vesting")]
+    RevokeNotAllowed,
+    #[msg("The beneficiary does not match the vesting")]
+    InvalidBeneficiary,
+    #[msg("The creator does not match the vesting")]
+    InvalidCreator,
+    #[msg("Arbitrated escrows settle through release or resolve")]
+    ArbitratedEscrow,
+    #[msg("This escrow has no arbiter")]
+    NotArbitrated,
+    #[msg("Only the arbiter can resolve this escrow")]
+    InvalidArbiter,
+    #[msg("The payee does not match the escrow")]
+    InvalidPayee,
+    #[msg("Only the maker or the payee can raise a dispute")]
+    DisputeNotAllowed,
+    #[msg("The escrow is already disputed")]
+    AlreadyDisputed,
+    #[msg("The escrow is not disputed")]
+    NotDisputed,
+    #[msg("The payee share must be at most 10000 basis points")]
+    InvalidShare,
+    #[msg("Milestones need a short name, a non-zero amount and must fit in the vault")]
+    InvalidMilestone,
+    #[msg("Too many milestones on this escrow")]
+    TooManyMilestones,
+    #[msg("This milestone has already been approved")]
+    MilestoneApproved,
+    #[msg("Milestones can only be refunded after the deadline or once they paid out the deposit")]
+    MilestonesNotDue,
+    #[msg("Auctions need start >= floor > 0 and a start before the end")]
+    InvalidAuction,
+    #[msg("Dutch auction offers can only be taken whole")]
+    AuctionNotSupported,
+    #[msg("The current price is above the taker's maximum")]
+    SlippageExceeded,
+    #[msg("Offer accounts must come as escrow, vault, maker, maker ATA and maker profile")]
+    InvalidOfferAccounts,
+    #[msg("Non-transferable and permanent-delegate mints can't be escrowed")]
+    UnsupportedMint,
+    #[msg("Transfer-hook mints aren't supported by this instruction")]
+    TransferHookNotSupported,
+    #[msg("The attestation account doesn't match the escrow's condition")]
+    InvalidAttestation,
+    #[msg("The attested value doesn't satisfy the escrow's condition")]
+    ConditionNotMet,
+    #[msg("Conditional offers can only be taken whole with their attestation")]
+    ConditionalEscrow,
+    #[msg("The NFT isn't a verified member of the offer's collection")]
+    InvalidCollection,
+    #[msg("Collection bids can only be filled with take_collection_bid")]
+    CollectionBid,
+    #[msg("Token accounts requiring a memo on incoming transfers aren't supported")]
+    MemoTransferNotSupported,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+}
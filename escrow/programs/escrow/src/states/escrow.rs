@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token::native_mint;
+
+use crate::states::{Condition, EscrowError};
 
 #[account]
 #[derive(InitSpace)]
@@ -8,5 +11,115 @@ pub struct EscrowState {
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    pub expires_at: Option<i64>, // Unix timestamp after which the offer can't be taken
+    pub filled_amount: u64,      // Mint B already paid to the maker by partial takes
+    pub allowed_taker: Option<Pubkey>, // Only this signer may take when set
+    pub arbitration: Option<Arbitration>, // Settled by release or resolve instead of a take
+    pub auction: Option<DutchAuction>, // Replaces receive_amount with a decaying price
+    pub condition: Option<Condition>, // Takes need an attestation satisfying it
+    pub collection_bid: bool,    // mint_b is a collection, any verified member NFT fills the offer
     pub bump: u8,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct Arbitration {
+    pub arbiter: Pubkey,
+    pub payee: Pubkey,
+    pub disputed: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct DutchAuction {
+    pub start_receive_amount: u64,
+    pub floor_receive_amount: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+}
+
+impl DutchAuction {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.start_receive_amount >= self.floor_receive_amount
+                && self.floor_receive_amount != 0
+                && self.start_ts < self.end_ts,
+            EscrowError::InvalidAuction
+        );
+        Ok(())
+    }
+
+    // Linear decay from the start to the floor amount, rounded in the maker's favour
+    pub fn price_at(&self, now: i64) -> Option<u64> {
+        if now <= self.start_ts {
+            return Some(self.start_receive_amount);
+        }
+        if now >= self.end_ts {
+            return Some(self.floor_receive_amount);
+        }
+
+        let elapsed = now.checked_sub(self.start_ts)? as u128;
+        let duration = self.end_ts.checked_sub(self.start_ts)? as u128;
+        let decay = ((self.start_receive_amount - self.floor_receive_amount) as u128)
+            .checked_mul(elapsed)?
+            .checked_div(duration)?;
+        self.start_receive_amount
+            .checked_sub(u64::try_from(decay).ok()?)
+    }
+}
+
+impl EscrowState {
+    // Native SOL is offered as lamports held by the escrow account itself, with no vault
+    pub fn offers_native(&self) -> bool {
+        is_native_mint(&self.mint_a)
+    }
+
+    // Native SOL is paid straight to the maker's wallet with a system transfer
+    pub fn asks_native(&self) -> bool {
+        is_native_mint(&self.mint_b)
+    }
+
+    pub fn is_arbitrated(&self) -> bool {
+        self.arbitration.is_some()
+    }
+
+    // `Option::is_none_or` needs Rust 1.82, newer than the Solana toolchain
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn is_allowed_taker(&self, taker: &Pubkey) -> bool {
+        self.allowed_taker.map_or(true, |allowed| allowed == *taker)
+    }
+
+    pub fn is_auction(&self) -> bool {
+        self.auction.is_some()
+    }
+
+    pub fn is_conditional(&self) -> bool {
+        self.condition.is_some()
+    }
+
+    // What a whole take pays at `now`, the auction price or the unfilled receive amount
+    pub fn receive_amount_at(&self, now: i64) -> Option<u64> {
+        match &self.auction {
+            Some(auction) => auction.price_at(now),
+            None => Some(self.remaining_receive_amount()),
+        }
+    }
+
+    pub fn remaining_receive_amount(&self) -> u64 {
+        self.receive_amount.saturating_sub(self.filled_amount)
+    }
+
+    // Mint A owed for `amount_b`, pro rata to what is left in the vault and rounded down
+    pub fn fill_share(&self, amount_b: u64, vault_amount: u64) -> Option<u64> {
+        let share = (vault_amount as u128)
+            .checked_mul(amount_b as u128)?
+            .checked_div(self.remaining_receive_amount() as u128)?;
+        u64::try_from(share).ok()
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == native_mint::ID
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+
+use crate::states::{Arbitration, BundleLeg, Condition, DutchAuction};
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferMade {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub seed: u64,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit_amount: u64,
+    pub receive_amount: u64,
+    pub expires_at: Option<i64>,
+    pub allowed_taker: Option<Pubkey>,
+    pub auction: Option<DutchAuction>,
+    pub arbitration: Option<Arbitration>,
+    pub condition: Option<Condition>,
+    pub collection_bid: bool,
+}
+
+// Emitted for whole takes and for every partial fill, with `filled` set once closed
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferTaken {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub recipient: Pubkey, // Received the deposit, the taker unless they named someone else
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount_a: u64, // Released from the vault, fee included
+    pub amount_b: u64, // Paid by the taker, fee included
+    pub fee: u64,
+    pub fee_on_mint_a: bool,
+    pub filled: bool,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferRefunded {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub caller: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount: u64,
+}
+
+// Emitted when an arbitrated escrow closes through release, resolve or a milestone refund
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferSettled {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub payee: Pubkey,
+    pub mint_a: Pubkey,
+    pub payee_amount: u64, // Paid out by this settlement, approved milestones excluded
+    pub maker_amount: u64,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct BundleMade {
+    pub bundle: Pubkey,
+    pub maker: Pubkey,
+    pub seed: u64,
+    pub offer: Vec<BundleLeg>,
+    pub ask: Vec<BundleLeg>,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct BundleTaken {
+    pub bundle: Pubkey,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub offer: Vec<BundleLeg>, // Leg amounts, the vaults may have held more
+    pub ask: Vec<BundleLeg>,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct BundleRefunded {
+    pub bundle: Pubkey,
+    pub maker: Pubkey,
+    pub offer: Vec<BundleLeg>,
+}
+
+#[event]
+#[derive(Clone, Debug)]
+pub struct OfferAmended {
+    pub escrow: Pubkey,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub deposit_amount: u64,
+    pub receive_amount: u64,
+    pub filled_amount: u64,
+    pub expires_at: Option<i64>,
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MakerProfile {
+    pub maker: Pubkey,
+    pub offer_count: u64, // Seed of the maker's next escrow, offers use 0..offer_count
+    pub filled_count: u64,
+    pub refunded_count: u64,
+    pub bump: u8,
+}
+
+impl MakerProfile {
+    // Offers that have been neither filled nor refunded yet
+    pub fn open_count(&self) -> u64 {
+        self.offer_count
+            .saturating_sub(self.filled_count)
+            .saturating_sub(self.refunded_count)
+    }
+}
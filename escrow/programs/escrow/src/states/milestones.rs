@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+pub const MAX_MILESTONES: usize = 16;
+pub const MAX_MILESTONE_NAME_LEN: usize = 32;
+
+// Extension of an arbitrated `EscrowState` that splits its vault into milestones, grown
+// with realloc as milestones are added
+#[account]
+pub struct MilestonePlan {
+    pub escrow: Pubkey,
+    pub deadline: i64, // After this the unapproved remainder can be refunded to the maker
+    pub bump: u8,
+    pub milestones: Vec<Milestone>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct Milestone {
+    #[max_len(MAX_MILESTONE_NAME_LEN)]
+    pub name: String,
+    pub amount: u64,
+    pub approved: bool,
+}
+
+impl MilestonePlan {
+    pub fn space(count: usize) -> usize {
+        8 + 32 + 8 + 1 + 4 + count * Milestone::INIT_SPACE
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &Milestone> {
+        self.milestones
+            .iter()
+            .filter(|milestone| !milestone.approved)
+    }
+
+    pub fn pending_amount(&self) -> Option<u64> {
+        self.pending()
+            .try_fold(0u64, |total, milestone| total.checked_add(milestone.amount))
+    }
+
+    // Every milestone is approved and together they paid out the whole deposit, leaving
+    // `vault_amount` empty. Only a complete plan settles before its deadline
+    pub fn is_complete(&self, vault_amount: u64) -> bool {
+        !self.milestones.is_empty() && self.pending().next().is_none() && vault_amount == 0
+    }
+
+    // Closes the plan of an escrow that is being settled, if one was ever created
+    pub fn close_if_exists<'info>(
+        plan: &AccountInfo<'info>,
+        destination: &AccountInfo<'info>,
+    ) -> Result<()> {
+        if plan.owner != &crate::ID {
+            return Ok(());
+        }
+
+        let lamports = plan.lamports();
+        **plan.try_borrow_mut_lamports()? = 0;
+        **destination.try_borrow_mut_lamports()? = destination
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(crate::states::EscrowError::Overflow)?;
+
+        plan.assign(&system_program::ID);
+        plan.realloc(0, false)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(milestones: &[(u64, bool)]) -> MilestonePlan {
+        MilestonePlan {
+            escrow: Pubkey::new_unique(),
+            deadline: 0,
+            bump: 0,
+            milestones: milestones
+                .iter()
+                .map(|&(amount, approved)| Milestone {
+                    name: String::new(),
+                    amount,
+                    approved,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn an_empty_plan_is_never_complete() {
+        assert!(!plan(&[]).is_complete(0));
+        assert!(!plan(&[]).is_complete(100));
+    }
+
+    #[test]
+    fn a_plan_covering_part_of_the_deposit_is_not_complete() {
+        // Both milestones approved, but 40 of the 100 deposited were never planned
+        assert!(!plan(&[(40, true), (20, true)]).is_complete(40));
+        assert!(!plan(&[(40, true), (60, false)]).is_complete(60));
+    }
+
+    #[test]
+    fn a_plan_paying_out_the_whole_deposit_is_complete() {
+        assert!(plan(&[(40, true), (60, true)]).is_complete(0));
+    }
+}
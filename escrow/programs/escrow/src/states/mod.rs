@@ -1,2 +1,18 @@
+pub mod bundle;
+pub mod condition;
+pub mod config;
+pub mod errors;
 pub mod escrow;
+pub mod events;
+pub mod maker_profile;
+pub mod milestones;
+pub mod vesting;
+pub use bundle::*;
+pub use condition::*;
+pub use config::*;
+pub use errors::*;
 pub use escrow::*;
+pub use events::*;
+pub use maker_profile::*;
+pub use milestones::*;
+pub use vesting::*;
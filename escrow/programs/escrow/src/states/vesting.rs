@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::states::EscrowError;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vesting {
+    pub seed: u64,
+    pub creator: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint_a: Pubkey,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub start_ts: i64,
+    pub cliff_ts: i64, // Nothing unlocks before this, then everything vested since start does
+    pub end_ts: i64,
+    pub revoke_authority: Option<Pubkey>, // May claw back unvested tokens when set
+    pub bump: u8,
+}
+
+impl Vesting {
+    pub fn validate_schedule(start_ts: i64, cliff_ts: i64, end_ts: i64) -> Result<()> {
+        require!(
+            start_ts <= cliff_ts && cliff_ts <= end_ts && start_ts < end_ts,
+            EscrowError::InvalidSchedule
+        );
+        Ok(())
+    }
+
+    // Amount unlocked at `now`, linear between start and end and rounded down
+    pub fn vested_amount(&self, now: i64) -> Option<u64> {
+        if now < self.cliff_ts {
+            return Some(0);
+        }
+        if now >= self.end_ts {
+            return Some(self.total_amount);
+        }
+
+        let elapsed = now.checked_sub(self.start_ts)? as u128;
+        let duration = self.end_ts.checked_sub(self.start_ts)? as u128;
+        let vested = (self.total_amount as u128)
+            .checked_mul(elapsed)?
+            .checked_div(duration)?;
+        u64::try_from(vested).ok()
+    }
+
+    pub fn claimable_amount(&self, now: i64) -> Option<u64> {
+        self.vested_amount(now)?.checked_sub(self.claimed_amount)
+    }
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+declare_id!("9z6YZK5xdB126ywWsmhVWErz9uGshsTbAgMUDDVyMacb");
+
+// Stand-in for a price or outcome oracle: each authority publishes one value that conditional
+// escrows read back with the `DiscriminatedValue` layout
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    pub fn publish(ctx: Context<Publish>, value: i64) -> Result<()> {
+        ctx.accounts.attestation.set_inner(Attestation {
+            value,
+            published_at: Clock::get()?.unix_timestamp,
+            authority: ctx.accounts.authority.key(),
+            bump: ctx.bumps.attestation,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Publish<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = 8 + Attestation::INIT_SPACE,
+      seeds = [b"attestation", authority.key().as_ref()],
+      bump,
+  )]
+    pub attestation: Account<'info, Attestation>,
+    pub system_program: Program<'info, System>,
+}
+
+// The value must stay first so it sits right after the discriminator
+#[account]
+#[derive(InitSpace)]
+pub struct Attestation {
+    pub value: i64,
+    pub published_at: i64,
+    pub authority: Pubkey,
+    pub bump: u8,
+}
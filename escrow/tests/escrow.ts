@@ -1,16 +1,17 @@
 import * as anchor from "@coral-xyz/anchor";
 import { BN, Program } from "@coral-xyz/anchor";
-import { PublicKey, SystemProgram } from "@solana/web3.js";
-import { createAssociatedTokenAccountIdempotentInstruction, createInitializeMint2Instruction, createMintToInstruction, getAssociatedTokenAddressSync, getMinimumBalanceForRentExemptMint, MINT_SIZE, TOKEN_PROGRAM_ID } from "@solana/spl-token";
-import { randomBytes } from "crypto";
+import { BPF_LOADER_UPGRADEABLE_PROGRAM_ID, PublicKey, SystemProgram } from "@solana/web3.js";
+import { createAssociatedTokenAccountIdempotentInstruction, createInitializeMint2Instruction, createInitializeNonTransferableMintInstruction, createInitializeTransferFeeConfigInstruction, createMintToInstruction, createTransferCheckedInstruction, ExtensionType, getAssociatedTokenAddressSync, getMinimumBalanceForRentExemptMint, getMintLen, MINT_SIZE, NATIVE_MINT, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { assert } from "chai";
 import { Escrow } from "../target/types/escrow";
+import { MockOracle } from "../target/types/mock_oracle";
 
 describe("escrow", () => {
   anchor.setProvider(anchor.AnchorProvider.env());
   const provider = anchor.getProvider();
 
   const program = anchor.workspace.Escrow as Program<Escrow>;
+  const oracle = anchor.workspace.MockOracle as Program<MockOracle>;
   const tokenProgram = TOKEN_PROGRAM_ID;
 
   const alice = anchor.web3.Keypair.generate();
@@ -18,7 +19,7 @@ describe("escrow", () => {
   const mintA = anchor.web3.Keypair.generate();
   const mintB = anchor.web3.Keypair.generate();
 
-  const seed = new BN(randomBytes(8));
+  const seed = new BN(0); // alice's first offer
   const depositAmount = new BN(100_000_000)
   const receiveAmount = new BN(120_000_000)
   const tokenAmount = 1_000_000_000
@@ -31,6 +32,10 @@ describe("escrow", () => {
     program.programId
   )
   const vaultPublicKey = getAssociatedTokenAddressSync(mintA.publicKey, escrowPublicKey, true, tokenProgram);
+  const [treasuryPublicKey] = PublicKey.findProgramAddressSync([Buffer.from("treasury")], program.programId)
+  const treasuryMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, treasuryPublicKey, true, tokenProgram);
+  const [programDataPublicKey] = PublicKey.findProgramAddressSync([program.programId.toBuffer()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID)
+  const feeBps = 100
 
   const accounts = {
     maker: alice.publicKey,
@@ -41,9 +46,22 @@ describe("escrow", () => {
     takerMintABta: bobMintBPublicKey,
     escrow: escrowPublicKey,
     vault: vaultPublicKey,
+    treasuryMintBAta: treasuryMintBPublicKey,
     tokenProgram,
   }
 
+  const nextOfferSeed = async (maker: PublicKey) => {
+    const [profile] = PublicKey.findProgramAddressSync([Buffer.from("maker"), maker.toBuffer()], program.programId);
+    const account = await program.account.makerProfile.fetchNullable(profile);
+    return account ? account.offerCount : new BN(0);
+  }
+
+  const eventsOf = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const transaction = await provider.connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    return [...new anchor.EventParser(program.programId, program.coder).parseLogs(transaction.meta.logMessages)];
+  }
+
   it('should create mints', async () => {
     let lamports = await getMinimumBalanceForRentExemptMint(program.provider.connection);
     await airdrop(provider.connection, alice.publicKey);
@@ -98,8 +116,32 @@ describe("escrow", () => {
     assert.strictEqual(aliceMintABalance.value.amount, tokenAmount.toString())
   })
 
+  it('should charge a protocol fee configured by the upgrade authority', async () => {
+    try {
+      await program.methods.initializeConfig(feeBps, false).accountsPartial({ admin: bob.publicKey, programData: programDataPublicKey }).signers([bob]).rpc()
+      assert.fail("only the upgrade authority may initialize the config")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidAdmin")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.initializeConfig(feeBps, false).accountsPartial({ admin: provider.publicKey, programData: programDataPublicKey }).rpc()
+    )
+
+    try {
+      await program.methods.updateConfig(5_000, false).accountsPartial({ admin: provider.publicKey }).rpc()
+      assert.fail("fees above the cap should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "FeeTooHigh")
+    }
+
+    const config = await program.account.escrowConfig.fetch(PublicKey.findProgramAddressSync([Buffer.from("config")], program.programId)[0]);
+    assert.strictEqual(config.feeBps, feeBps)
+  })
+
   it('should let alice deposit to the escrow\'s vault', async () => {
-    const signature = await program.methods.make(seed, receiveAmount, depositAmount).accounts({ ...accounts }).signers([alice]).rpc()
+    const signature = await program.methods.make(receiveAmount, depositAmount, null, null).accounts({ ...accounts }).signers([alice]).rpc()
     await confirmTransaction(provider.connection, signature)
 
     const vaultBalance = await provider.connection.getTokenAccountBalance(vaultPublicKey);
@@ -108,14 +150,819 @@ describe("escrow", () => {
     assert.strictEqual(vaultBalance.value.amount, depositAmount.toString())
   })
 
+  it('should reject takes and refunds that swap in a different mint', async () => {
+    const mintC = anchor.web3.Keypair.generate();
+    const bobMintCPublicKey = getAssociatedTokenAddressSync(mintC.publicKey, bob.publicKey, false, tokenProgram);
+    const aliceMintCPublicKey = getAssociatedTokenAddressSync(mintC.publicKey, alice.publicKey, false, tokenProgram);
+
+    const transaction = new anchor.web3.Transaction();
+    transaction.instructions = [
+      SystemProgram.createAccount({
+        fromPubkey: provider.publicKey,
+        newAccountPubkey: mintC.publicKey,
+        lamports: await getMinimumBalanceForRentExemptMint(provider.connection),
+        space: MINT_SIZE,
+        programId: tokenProgram,
+      }),
+      createInitializeMint2Instruction(mintC.publicKey, 6, bob.publicKey, null, tokenProgram),
+      createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, bobMintCPublicKey, bob.publicKey, mintC.publicKey, tokenProgram),
+      createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, aliceMintCPublicKey, alice.publicKey, mintC.publicKey, tokenProgram),
+      createMintToInstruction(mintC.publicKey, bobMintCPublicKey, bob.publicKey, tokenAmount, undefined, tokenProgram),
+    ];
+    await provider.sendAndConfirm(transaction, [bob, mintC]);
+
+    try {
+      await program.methods.take(receiveAmount).accountsPartial({ ...accounts, mintB: mintC.publicKey, treasuryMintBAta: null }).signers([bob]).rpc()
+      assert.fail("paying with a worthless mint should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidMintB")
+    }
+
+    try {
+      await program.methods.refund()
+        .accountsPartial({ ...accounts, caller: alice.publicKey, mintA: mintC.publicKey, makerMintAAta: aliceMintCPublicKey })
+        .signers([alice])
+        .rpc()
+      assert.fail("refunding against another mint should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidMintA")
+    }
+  })
+
   it('should send bob deposit from vault after purchasing', async () => {
-    const signature = await program.methods.take().accountsPartial({ ...accounts }).signers([bob]).rpc()
+    const signature = await program.methods.take(receiveAmount).accountsPartial({ ...accounts }).signers([bob]).rpc()
     await confirmTransaction(provider.connection, signature)
 
     const bobMintABalance = await provider.connection.getTokenAccountBalance(bobMintAPublicKey);
     // console.log("Bob Mint A Balance :", bobMintABalance);
 
     assert.strictEqual(bobMintABalance.value.amount, depositAmount.toString())
+
+    const fee = receiveAmount.muln(feeBps).divn(10_000);
+    const treasuryBalance = await provider.connection.getTokenAccountBalance(treasuryMintBPublicKey);
+    assert.strictEqual(treasuryBalance.value.amount, fee.toString())
+
+    const adminMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, provider.publicKey, false, tokenProgram);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.withdrawTreasury(fee).accountsPartial({ admin: provider.publicKey, mint: mintB.publicKey, treasuryAta: treasuryMintBPublicKey, adminAta: adminMintBPublicKey, tokenProgram }).rpc()
+    )
+    assert.strictEqual((await provider.connection.getTokenAccountBalance(adminMintBPublicKey)).value.amount, fee.toString())
+  })
+
+  it('should let alice amend the price and deposit of an open offer', async () => {
+    const amendSeed = await nextOfferSeed(alice.publicKey);
+    const [amendEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), amendSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const amendVault = getAssociatedTokenAddressSync(mintA.publicKey, amendEscrow, true, tokenProgram);
+    const amendAccounts = { ...accounts, escrow: amendEscrow, vault: amendVault };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null).accounts(amendAccounts).signers([alice]).rpc()
+    )
+
+    const newReceiveAmount = receiveAmount.muln(2);
+    const topUp = depositAmount.divn(2);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.amend(newReceiveAmount, topUp, new BN(0), null).accountsPartial(amendAccounts).signers([alice]).rpc()
+    )
+
+    const escrow = await program.account.escrowState.fetch(amendEscrow);
+    assert.strictEqual(escrow.receiveAmount.toString(), newReceiveAmount.toString())
+    assert.strictEqual(
+      (await provider.connection.getTokenAccountBalance(amendVault)).value.amount,
+      depositAmount.add(topUp).toString()
+    )
+
+    await program.methods.refund().accountsPartial({ ...amendAccounts, caller: alice.publicKey }).signers([alice]).rpc()
+  })
+
+  it('should let alice top up and withdraw from a native SOL offer', async () => {
+    const nativeSeed = await nextOfferSeed(alice.publicKey);
+    const [nativeEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), nativeSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const nativeAccounts = { maker: alice.publicKey, mintA: NATIVE_MINT, mintB: mintB.publicKey, makerMintAAta: null, escrow: nativeEscrow, vault: null, tokenProgram };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null).accountsPartial(nativeAccounts).signers([alice]).rpc()
+    )
+    const escrowLamports = await provider.connection.getBalance(nativeEscrow);
+
+    const topUp = depositAmount.divn(2);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.amend(null, topUp, new BN(0), null).accountsPartial(nativeAccounts).signers([alice]).rpc()
+    )
+    assert.strictEqual(await provider.connection.getBalance(nativeEscrow), escrowLamports + topUp.toNumber())
+
+    const withdrawn = depositAmount;
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.amend(null, new BN(0), withdrawn, null).accountsPartial(nativeAccounts).signers([alice]).rpc()
+    )
+    assert.strictEqual(await provider.connection.getBalance(nativeEscrow), escrowLamports + topUp.toNumber() - withdrawn.toNumber())
+
+    // The whole deposit can't be withdrawn, that's a refund
+    try {
+      await program.methods.amend(null, new BN(0), topUp, null).accountsPartial(nativeAccounts).signers([alice]).rpc()
+      assert.fail("withdrew the whole deposit")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidWithdrawAmount")
+    }
+
+    await program.methods.refund().accountsPartial({ ...nativeAccounts, caller: alice.publicKey }).signers([alice]).rpc()
+  })
+
+  it('should fill an offer across several partial takes', async () => {
+    const partialSeed = await nextOfferSeed(alice.publicKey);
+    const [partialEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), partialSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const partialVault = getAssociatedTokenAddressSync(mintA.publicKey, partialEscrow, true, tokenProgram);
+    const partialAccounts = { ...accounts, escrow: partialEscrow, vault: partialVault };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null).accounts(partialAccounts).signers([alice]).rpc()
+    )
+    const before = await provider.connection.getTokenAccountBalance(bobMintAPublicKey);
+
+    // a quarter of the receive amount buys a quarter of the deposit
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.takePartial(receiveAmount.divn(4)).accountsPartial(partialAccounts).signers([bob]).rpc()
+    )
+    const escrow = await program.account.escrowState.fetch(partialEscrow);
+    assert.strictEqual(escrow.filledAmount.toString(), receiveAmount.divn(4).toString())
+    assert.strictEqual(
+      (await provider.connection.getTokenAccountBalance(partialVault)).value.amount,
+      depositAmount.sub(depositAmount.divn(4)).toString()
+    )
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.takePartial(receiveAmount.sub(receiveAmount.divn(4))).accountsPartial(partialAccounts).signers([bob]).rpc()
+    )
+
+    const after = await provider.connection.getTokenAccountBalance(bobMintAPublicKey);
+    assert.strictEqual(new BN(after.value.amount).sub(new BN(before.value.amount)).toString(), depositAmount.toString())
+    assert.isNull(await provider.connection.getAccountInfo(partialEscrow))
+  })
+
+  it('should reject takers other than the designated one on a private offer', async () => {
+    const privateSeed = await nextOfferSeed(alice.publicKey);
+    const [privateEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), privateSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const privateVault = getAssociatedTokenAddressSync(mintA.publicKey, privateEscrow, true, tokenProgram);
+    const privateAccounts = { ...accounts, escrow: privateEscrow, vault: privateVault };
+    const desk = anchor.web3.Keypair.generate();
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, desk.publicKey).accounts(privateAccounts).signers([alice]).rpc()
+    )
+
+    try {
+      await program.methods.take(receiveAmount).accountsPartial(privateAccounts).signers([bob]).rpc()
+      assert.fail("bob should not be able to take a private offer")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "TakerNotAllowed")
+    }
+
+    await program.methods.refund().accountsPartial({ ...privateAccounts, caller: alice.publicKey }).signers([alice]).rpc()
+  })
+
+  it('should let anyone refund alice once her offer has expired', async () => {
+    const expiringSeed = await nextOfferSeed(alice.publicKey);
+    const [expiringEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), expiringSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const expiringVault = getAssociatedTokenAddressSync(mintA.publicKey, expiringEscrow, true, tokenProgram);
+    const expiresAt = new BN(Math.floor(Date.now() / 1000) + 2);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, expiresAt, null)
+        .accounts({ ...accounts, escrow: expiringEscrow, vault: expiringVault })
+        .signers([alice])
+        .rpc()
+    )
+    const before = await provider.connection.getTokenAccountBalance(aliceMintAPublicKey);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.refund()
+        .accountsPartial({ caller: bob.publicKey, maker: alice.publicKey, mintA: mintA.publicKey, mintB: mintB.publicKey, makerMintAAta: aliceMintAPublicKey, escrow: expiringEscrow, vault: expiringVault, tokenProgram })
+        .signers([bob])
+        .rpc()
+    )
+
+    const after = await provider.connection.getTokenAccountBalance(aliceMintAPublicKey);
+    assert.strictEqual(new BN(after.value.amount).sub(new BN(before.value.amount)).toString(), depositAmount.toString())
+    assert.isNull(await provider.connection.getAccountInfo(expiringEscrow))
+  })
+  it('should swap native SOL without any wrapped SOL accounts', async () => {
+    const aliceMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, alice.publicKey, false, tokenProgram);
+
+    // alice offers lamports for mint B
+    const solSeed = await nextOfferSeed(alice.publicKey);
+    const [solEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), solSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null)
+        .accountsPartial({ maker: alice.publicKey, mintA: NATIVE_MINT, mintB: mintB.publicKey, makerMintAAta: null, escrow: solEscrow, vault: null, tokenProgram })
+        .signers([alice])
+        .rpc()
+    )
+    const escrowLamports = await provider.connection.getBalance(solEscrow);
+    const bobBefore = await provider.connection.getBalance(bob.publicKey);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.take(receiveAmount)
+        .accountsPartial({ taker: bob.publicKey, maker: alice.publicKey, mintA: NATIVE_MINT, mintB: mintB.publicKey, takerMintAAta: null, takerMintBAta: bobMintBPublicKey, makerMintBAta: aliceMintBPublicKey, escrow: solEscrow, vault: null, treasuryMintBAta: treasuryMintBPublicKey, tokenProgram })
+        .signers([bob])
+        .rpc()
+    )
+    assert.strictEqual(await provider.connection.getBalance(bob.publicKey) - bobBefore, escrowLamports)
+    assert.isNull(await provider.connection.getAccountInfo(solEscrow))
+
+    // alice asks for lamports in exchange for mint A
+    const askSeed = await nextOfferSeed(alice.publicKey);
+    const [askEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), askSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const askVault = getAssociatedTokenAddressSync(mintA.publicKey, askEscrow, true, tokenProgram);
+    const askLamports = new BN(50_000_000);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(askLamports, depositAmount, null, null)
+        .accountsPartial({ maker: alice.publicKey, mintA: mintA.publicKey, mintB: NATIVE_MINT, makerMintAAta: aliceMintAPublicKey, escrow: askEscrow, vault: askVault, tokenProgram })
+        .signers([alice])
+        .rpc()
+    )
+    const aliceBefore = await provider.connection.getBalance(alice.publicKey);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.take(askLamports)
+        .accountsPartial({ taker: bob.publicKey, maker: alice.publicKey, mintA: mintA.publicKey, mintB: NATIVE_MINT, takerMintAAta: bobMintAPublicKey, takerMintBAta: null, makerMintBAta: null, escrow: askEscrow, vault: askVault, treasuryMintBAta: null, tokenProgram })
+        .signers([bob])
+        .rpc()
+    )
+    assert.strictEqual(await provider.connection.getBalance(alice.publicKey) - aliceBefore, askLamports.toNumber() * (10_000 - feeBps) / 10_000)
+  })
+
+  it('should sell a Dutch auction offer at the decayed price', async () => {
+    const auctionSeed = await nextOfferSeed(alice.publicKey);
+    const [auctionEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), auctionSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const auctionVault = getAssociatedTokenAddressSync(mintA.publicKey, auctionEscrow, true, tokenProgram);
+    const auctionAccounts = { ...accounts, escrow: auctionEscrow, vault: auctionVault };
+    const aliceMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, alice.publicKey, false, tokenProgram);
+
+    // already at the floor, so the price is deterministic
+    const now = Math.floor(Date.now() / 1000);
+    const auction = { startReceiveAmount: receiveAmount.muln(2), floorReceiveAmount: receiveAmount, startTs: new BN(now - 200), endTs: new BN(now - 100) };
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeAuction(depositAmount, auction, null, null).accountsPartial(auctionAccounts).signers([alice]).rpc()
+    )
+
+    try {
+      await program.methods.take(receiveAmount.subn(1)).accountsPartial(auctionAccounts).signers([bob]).rpc()
+      assert.fail("a max pay below the current price should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "SlippageExceeded")
+    }
+
+    const before = new BN((await provider.connection.getTokenAccountBalance(aliceMintBPublicKey)).value.amount);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.take(receiveAmount.muln(2)).accountsPartial(auctionAccounts).signers([bob]).rpc()
+    )
+    const after = new BN((await provider.connection.getTokenAccountBalance(aliceMintBPublicKey)).value.amount);
+
+    const fee = receiveAmount.muln(feeBps).divn(10_000);
+    assert.strictEqual(after.sub(before).toString(), receiveAmount.sub(fee).toString())
+  })
+
+  it('should sweep several offers in one take_many', async () => {
+    const aliceMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, alice.publicKey, false, tokenProgram);
+    const [aliceProfile] = PublicKey.findProgramAddressSync([Buffer.from("maker"), alice.publicKey.toBuffer()], program.programId);
+    const offers = [];
+
+    for (let i = 0; i < 2; i++) {
+      const offerSeed = await nextOfferSeed(alice.publicKey);
+      const [offerEscrow] = PublicKey.findProgramAddressSync(
+        [Buffer.from("escrow"), alice.publicKey.toBuffer(), offerSeed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )
+      const offerVault = getAssociatedTokenAddressSync(mintA.publicKey, offerEscrow, true, tokenProgram);
+      await confirmTransaction(
+        provider.connection,
+        await program.methods.make(receiveAmount, depositAmount, null, null).accountsPartial({ ...accounts, escrow: offerEscrow, vault: offerVault }).signers([alice]).rpc()
+      )
+      offers.push(
+        { pubkey: offerEscrow, isSigner: false, isWritable: true },
+        { pubkey: offerVault, isSigner: false, isWritable: true },
+        { pubkey: alice.publicKey, isSigner: false, isWritable: false },
+        { pubkey: aliceMintBPublicKey, isSigner: false, isWritable: true },
+        { pubkey: aliceProfile, isSigner: false, isWritable: true },
+      )
+    }
+    const takeManyAccounts = { taker: bob.publicKey, mintA: mintA.publicKey, mintB: mintB.publicKey, takerMintAAta: bobMintAPublicKey, takerMintBAta: bobMintBPublicKey, treasuryMintAAta: null, treasuryMintBAta: treasuryMintBPublicKey, tokenProgram };
+
+    try {
+      await program.methods.takeMany(receiveAmount.muln(2).subn(1)).accountsPartial(takeManyAccounts).remainingAccounts(offers).signers([bob]).rpc()
+      assert.fail("the batch should respect the total max spend")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "SlippageExceeded")
+    }
+
+    const before = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.takeMany(receiveAmount.muln(2)).accountsPartial(takeManyAccounts).remainingAccounts(offers).signers([bob]).rpc()
+    )
+    const after = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+
+    assert.strictEqual(after.sub(before).toString(), depositAmount.muln(2).toString())
+    assert.isNull(await provider.connection.getAccountInfo(offers[0].pubkey))
+    assert.isNull(await provider.connection.getAccountInfo(offers[5].pubkey))
+  })
+
+  it('should pay the maker in full when the asked Token-2022 mint charges a transfer fee', async () => {
+    const token2022 = TOKEN_2022_PROGRAM_ID;
+    const plainMint = anchor.web3.Keypair.generate();
+    const feeMint = anchor.web3.Keypair.generate();
+    const lockedMint = anchor.web3.Keypair.generate();
+    const transferFeeBps = 50;
+
+    const plainLen = getMintLen([]);
+    const feeLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lockedLen = getMintLen([ExtensionType.NonTransferable]);
+    const rent = (len: number) => provider.connection.getMinimumBalanceForRentExemption(len);
+
+    const alicePlain = getAssociatedTokenAddressSync(plainMint.publicKey, alice.publicKey, false, token2022);
+    const aliceFee = getAssociatedTokenAddressSync(feeMint.publicKey, alice.publicKey, false, token2022);
+    const bobFee = getAssociatedTokenAddressSync(feeMint.publicKey, bob.publicKey, false, token2022);
+    const aliceLocked = getAssociatedTokenAddressSync(lockedMint.publicKey, alice.publicKey, false, token2022);
+
+    const transaction = new anchor.web3.Transaction();
+    transaction.instructions = [
+      SystemProgram.createAccount({ fromPubkey: provider.publicKey, newAccountPubkey: plainMint.publicKey, lamports: await rent(plainLen), space: plainLen, programId: token2022 }),
+      createInitializeMint2Instruction(plainMint.publicKey, 6, alice.publicKey, null, token2022),
+      createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, alicePlain, alice.publicKey, plainMint.publicKey, token2022),
+      createMintToInstruction(plainMint.publicKey, alicePlain, alice.publicKey, tokenAmount, undefined, token2022),
+
+      SystemProgram.createAccount({ fromPubkey: provider.publicKey, newAccountPubkey: feeMint.publicKey, lamports: await rent(feeLen), space: feeLen, programId: token2022 }),
+      createInitializeTransferFeeConfigInstruction(feeMint.publicKey, bob.publicKey, bob.publicKey, transferFeeBps, BigInt(tokenAmount), token2022),
+      createInitializeMint2Instruction(feeMint.publicKey, 6, bob.publicKey, null, token2022),
+      createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, bobFee, bob.publicKey, feeMint.publicKey, token2022),
+      createMintToInstruction(feeMint.publicKey, bobFee, bob.publicKey, tokenAmount, undefined, token2022),
+
+      SystemProgram.createAccount({ fromPubkey: provider.publicKey, newAccountPubkey: lockedMint.publicKey, lamports: await rent(lockedLen), space: lockedLen, programId: token2022 }),
+      createInitializeNonTransferableMintInstruction(lockedMint.publicKey, token2022),
+      createInitializeMint2Instruction(lockedMint.publicKey, 6, alice.publicKey, null, token2022),
+      createAssociatedTokenAccountIdempotentInstruction(provider.publicKey, aliceLocked, alice.publicKey, lockedMint.publicKey, token2022),
+      createMintToInstruction(lockedMint.publicKey, aliceLocked, alice.publicKey, tokenAmount, undefined, token2022),
+    ];
+    await provider.sendAndConfirm(transaction, [alice, bob, plainMint, feeMint, lockedMint]);
+
+    const offerSeed = await nextOfferSeed(alice.publicKey);
+    const [offerEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), offerSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+
+    try {
+      await program.methods.make(receiveAmount, depositAmount, null, null)
+        .accountsPartial({ maker: alice.publicKey, mintA: lockedMint.publicKey, mintB: feeMint.publicKey, makerMintAAta: aliceLocked, escrow: offerEscrow, vault: getAssociatedTokenAddressSync(lockedMint.publicKey, offerEscrow, true, token2022), tokenProgram: token2022 })
+        .signers([alice])
+        .rpc()
+      assert.fail("non-transferable mints should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "UnsupportedMint")
+    }
+
+    const offerVault = getAssociatedTokenAddressSync(plainMint.publicKey, offerEscrow, true, token2022);
+    const offerAccounts = {
+      maker: alice.publicKey,
+      taker: bob.publicKey,
+      mintA: plainMint.publicKey,
+      mintB: feeMint.publicKey,
+      makerMintAAta: alicePlain,
+      takerMintAAta: getAssociatedTokenAddressSync(plainMint.publicKey, bob.publicKey, false, token2022),
+      takerMintBAta: bobFee,
+      makerMintBAta: aliceFee,
+      escrow: offerEscrow,
+      vault: offerVault,
+      treasuryMintBAta: getAssociatedTokenAddressSync(feeMint.publicKey, treasuryPublicKey, true, token2022),
+      tokenProgram: token2022,
+    };
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null).accountsPartial(offerAccounts).signers([alice]).rpc()
+    )
+    try {
+      await program.methods.take(receiveAmount).accountsPartial(offerAccounts).signers([bob]).rpc()
+      assert.fail("the transfer fee on top of the price should count against the max pay")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "SlippageExceeded")
+    }
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.take(receiveAmount.muln(101).divn(100)).accountsPartial(offerAccounts).signers([bob]).rpc()
+    )
+
+    const received = (await provider.connection.getTokenAccountBalance(aliceFee)).value.amount;
+    const fee = receiveAmount.muln(feeBps).divn(10_000);
+    assert.strictEqual(received, receiveAmount.sub(fee).toString())
+  })
+
+  it('should only sell a conditional offer once the attested value meets its threshold', async () => {
+    const [attestation] = PublicKey.findProgramAddressSync([Buffer.from("attestation"), provider.publicKey.toBuffer()], oracle.programId);
+    await confirmTransaction(provider.connection, await oracle.methods.publish(new BN(90)).accounts({ authority: provider.publicKey }).rpc())
+
+    const conditionSeed = await nextOfferSeed(alice.publicKey);
+    const [conditionEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), conditionSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const conditionAccounts = { ...accounts, escrow: conditionEscrow, vault: getAssociatedTokenAddressSync(mintA.publicKey, conditionEscrow, true, tokenProgram), attestation };
+    const condition = { oracle: oracle.programId, attestation, comparison: { atLeast: {} }, threshold: new BN(100) };
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeConditional(receiveAmount, depositAmount, condition, null, null).accountsPartial(conditionAccounts).signers([alice]).rpc()
+    )
+
+    try {
+      await program.methods.take(receiveAmount).accountsPartial(conditionAccounts).signers([bob]).rpc()
+      assert.fail("the offer should stay locked below the threshold")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ConditionNotMet")
+    }
+
+    await confirmTransaction(provider.connection, await oracle.methods.publish(new BN(100)).accounts({ authority: provider.publicKey }).rpc())
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.take(receiveAmount).accountsPartial(conditionAccounts).signers([bob]).rpc()
+    )
+    assert.isNull(await provider.connection.getAccountInfo(conditionEscrow))
+  })
+
+  it('should deliver alice\'s deposit to a recipient bob takes the offer for', async () => {
+    const recipientSeed = await nextOfferSeed(alice.publicKey);
+    const [recipientEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), recipientSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const recipientAccounts = { ...accounts, escrow: recipientEscrow, vault: getAssociatedTokenAddressSync(mintA.publicKey, recipientEscrow, true, tokenProgram) };
+    const wallet = anchor.web3.Keypair.generate();
+    const walletMintAPublicKey = getAssociatedTokenAddressSync(mintA.publicKey, wallet.publicKey, false, tokenProgram);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.make(receiveAmount, depositAmount, null, null).accounts(recipientAccounts).signers([alice]).rpc()
+    )
+    const before = await provider.connection.getTokenAccountBalance(bobMintAPublicKey);
+    const signature = await program.methods.take(receiveAmount)
+      .accountsPartial({ ...recipientAccounts, recipient: wallet.publicKey, recipientMintAAta: walletMintAPublicKey })
+      .signers([bob])
+      .rpc();
+    await confirmTransaction(provider.connection, signature)
+
+    const received = await provider.connection.getTokenAccountBalance(walletMintAPublicKey);
+    assert.strictEqual(received.value.amount, depositAmount.toString())
+    const after = await provider.connection.getTokenAccountBalance(bobMintAPublicKey);
+    assert.strictEqual(after.value.amount, before.value.amount)
+
+    const [taken] = (await eventsOf(signature)).filter((event) => event.name === "offerTaken");
+    assert.strictEqual(taken.data.taker.toString(), bob.publicKey.toString())
+    assert.strictEqual(taken.data.recipient.toString(), wallet.publicKey.toString())
+  })
+
+  it('should swap a bundle and refund one whose vault holds more than its leg', async () => {
+    const aliceMintBPublicKey = getAssociatedTokenAddressSync(mintB.publicKey, alice.publicKey, false, tokenProgram);
+    const offer = [{ mint: mintA.publicKey, amount: new BN(10_000_000) }];
+    const ask = [{ mint: mintB.publicKey, amount: new BN(5_000_000) }];
+    const bundleAccounts = (seed: BN) => {
+      const [bundle] = PublicKey.findProgramAddressSync(
+        [Buffer.from("bundle"), alice.publicKey.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )
+      return { bundle, vault: getAssociatedTokenAddressSync(mintA.publicKey, bundle, true, tokenProgram) };
+    }
+    const meta = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+    // Bundles take their seed from alice's maker profile, like her escrows
+    const makeBundle = async () => {
+      const seed = await nextOfferSeed(alice.publicKey);
+      const accounts = bundleAccounts(seed);
+      const signature = await program.methods.makeBundle(offer, ask)
+        .accountsPartial({ maker: alice.publicKey, bundle: accounts.bundle, tokenProgram })
+        .remainingAccounts([mintA.publicKey, aliceMintAPublicKey, accounts.vault].map(meta))
+        .signers([alice])
+        .rpc();
+      await confirmTransaction(provider.connection, signature)
+
+      const [made] = (await eventsOf(signature)).filter((event) => event.name === "bundleMade");
+      assert.strictEqual(made.data.bundle.toString(), accounts.bundle.toString())
+      assert.strictEqual(made.data.seed.toString(), seed.toString())
+
+      // Anyone can send a vault more than its leg, which must not keep it from closing
+      const dust = new anchor.web3.Transaction().add(
+        createTransferCheckedInstruction(aliceMintAPublicKey, mintA.publicKey, accounts.vault, alice.publicKey, 1, 6, [], tokenProgram)
+      );
+      await provider.sendAndConfirm(dust, [alice]);
+      return accounts;
+    }
+
+    const taken = await makeBundle();
+    const bobMintABefore = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    const takeSignature = await program.methods.takeBundle()
+      .accountsPartial({ taker: bob.publicKey, maker: alice.publicKey, bundle: taken.bundle, tokenProgram })
+      .remainingAccounts([
+        mintA.publicKey, taken.vault, bobMintAPublicKey,
+        mintB.publicKey, bobMintBPublicKey, aliceMintBPublicKey,
+      ].map(meta))
+      .signers([bob])
+      .rpc();
+    await confirmTransaction(provider.connection, takeSignature)
+    const bobMintAAfter = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    assert.strictEqual(bobMintAAfter.sub(bobMintABefore).toString(), offer[0].amount.addn(1).toString())
+    assert.isNull(await provider.connection.getAccountInfo(taken.vault))
+    assert.isNull(await provider.connection.getAccountInfo(taken.bundle))
+    const [bundleTaken] = (await eventsOf(takeSignature)).filter((event) => event.name === "bundleTaken");
+    assert.strictEqual(bundleTaken.data.taker.toString(), bob.publicKey.toString())
+
+    const aliceMintABefore = new BN((await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount);
+    const refunded = await makeBundle();
+    const refundSignature = await program.methods.refundBundle()
+      .accountsPartial({ maker: alice.publicKey, bundle: refunded.bundle, tokenProgram })
+      .remainingAccounts([mintA.publicKey, refunded.vault, aliceMintAPublicKey].map(meta))
+      .signers([alice])
+      .rpc();
+    await confirmTransaction(provider.connection, refundSignature)
+    assert.strictEqual(
+      (await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount,
+      aliceMintABefore.toString()
+    )
+    assert.isNull(await provider.connection.getAccountInfo(refunded.vault))
+    assert.isNull(await provider.connection.getAccountInfo(refunded.bundle))
+    const [bundleRefunded] = (await eventsOf(refundSignature)).filter((event) => event.name === "bundleRefunded");
+    assert.strictEqual(bundleRefunded.data.bundle.toString(), refunded.bundle.toString())
+  })
+
+  it('should let the arbiter split a disputed payment', async () => {
+    const disputeSeed = await nextOfferSeed(alice.publicKey);
+    const [disputeEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), disputeSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const disputeVault = getAssociatedTokenAddressSync(mintA.publicKey, disputeEscrow, true, tokenProgram);
+    const disputeAccounts = { maker: alice.publicKey, payee: bob.publicKey, mintA: mintA.publicKey, mintB: mintA.publicKey, makerMintAAta: aliceMintAPublicKey, payeeMintAAta: bobMintAPublicKey, escrow: disputeEscrow, vault: disputeVault, tokenProgram };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeArbitrated(depositAmount, provider.publicKey, bob.publicKey).accountsPartial(disputeAccounts).signers([alice]).rpc()
+    )
+
+    try {
+      await program.methods.take(new BN(0)).accountsPartial({ ...accounts, escrow: disputeEscrow, vault: disputeVault, mintB: mintA.publicKey, takerMintBAta: bobMintAPublicKey, makerMintBAta: aliceMintAPublicKey, treasuryMintBAta: null }).signers([bob]).rpc()
+      assert.fail("arbitrated escrows cannot be taken")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "ArbitratedEscrow")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.raiseDispute().accountsPartial({ caller: bob.publicKey, maker: alice.publicKey, escrow: disputeEscrow }).signers([bob]).rpc()
+    )
+
+    try {
+      await program.methods.resolve(10_000).accountsPartial({ ...disputeAccounts, arbiter: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("only the arbiter may resolve")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidArbiter")
+    }
+
+    const before = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    const signature = await program.methods.resolve(7_000).accountsPartial({ ...disputeAccounts, arbiter: provider.publicKey }).rpc();
+    await confirmTransaction(provider.connection, signature)
+    const after = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+
+    assert.strictEqual(after.sub(before).toString(), depositAmount.muln(7).divn(10).toString())
+    assert.isNull(await provider.connection.getAccountInfo(disputeEscrow))
+
+    const [settled] = (await eventsOf(signature)).filter((event) => event.name === "offerSettled");
+    assert.strictEqual(settled.data.escrow.toString(), disputeEscrow.toString())
+    assert.strictEqual(settled.data.payeeAmount.toString(), depositAmount.muln(7).divn(10).toString())
+    assert.strictEqual(settled.data.makerAmount.toString(), depositAmount.muln(3).divn(10).toString())
+  })
+
+  it('should let alice release an arbitrated payment to bob', async () => {
+    const releaseSeed = await nextOfferSeed(alice.publicKey);
+    const [releaseEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), releaseSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const releaseVault = getAssociatedTokenAddressSync(mintA.publicKey, releaseEscrow, true, tokenProgram);
+    const releaseAccounts = { maker: alice.publicKey, payee: bob.publicKey, mintA: mintA.publicKey, mintB: mintA.publicKey, makerMintAAta: aliceMintAPublicKey, payeeMintAAta: bobMintAPublicKey, escrow: releaseEscrow, vault: releaseVault, tokenProgram };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeArbitrated(depositAmount, provider.publicKey, bob.publicKey).accountsPartial(releaseAccounts).signers([alice]).rpc()
+    )
+    const signature = await program.methods.release().accountsPartial(releaseAccounts).signers([alice]).rpc();
+    await confirmTransaction(provider.connection, signature)
+    assert.isNull(await provider.connection.getAccountInfo(releaseEscrow))
+
+    const [settled] = (await eventsOf(signature)).filter((event) => event.name === "offerSettled");
+    assert.strictEqual(settled.data.payee.toString(), bob.publicKey.toString())
+    assert.strictEqual(settled.data.payeeAmount.toString(), depositAmount.toString())
+    assert.strictEqual(settled.data.makerAmount.toString(), "0")
+  })
+
+  it('should pay out an arbitrated escrow milestone by milestone', async () => {
+    const planSeed = await nextOfferSeed(alice.publicKey);
+    const [planEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), planSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const [plan] = PublicKey.findProgramAddressSync([Buffer.from("milestones"), planEscrow.toBuffer()], program.programId)
+    const planVault = getAssociatedTokenAddressSync(mintA.publicKey, planEscrow, true, tokenProgram);
+    const planAccounts = { maker: alice.publicKey, payee: bob.publicKey, mintA: mintA.publicKey, mintB: mintA.publicKey, makerMintAAta: aliceMintAPublicKey, payeeMintAAta: bobMintAPublicKey, escrow: planEscrow, vault: planVault, milestones: plan, tokenProgram };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeArbitrated(depositAmount, provider.publicKey, bob.publicKey).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.createMilestones(new BN(Math.floor(Date.now() / 1000) + 3600)).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    await program.methods.addMilestone("design", depositAmount.muln(4).divn(10)).accountsPartial(planAccounts).signers([alice]).rpc()
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.addMilestone("build", depositAmount.muln(6).divn(10)).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+
+    try {
+      await program.methods.addMilestone("extras", new BN(1)).accountsPartial(planAccounts).signers([alice]).rpc()
+      assert.fail("milestones beyond the vault should be rejected")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "InvalidMilestone")
+    }
+
+    try {
+      await program.methods.refundMilestones().accountsPartial({ ...planAccounts, caller: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("pending milestones cannot be refunded before the deadline")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MilestonesNotDue")
+    }
+
+    const before = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.approveMilestone(0).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    const milestones = (await program.account.milestonePlan.fetch(plan)).milestones;
+    assert.deepEqual(milestones.filter((milestone) => !milestone.approved).map((milestone) => milestone.name), ["build"])
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.approveMilestone(1).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    const after = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    assert.strictEqual(after.sub(before).toString(), depositAmount.toString())
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.refundMilestones().accountsPartial({ ...planAccounts, caller: bob.publicKey }).signers([bob]).rpc()
+    )
+    assert.isNull(await provider.connection.getAccountInfo(planEscrow))
+    assert.isNull(await provider.connection.getAccountInfo(plan))
+  })
+
+  it('should only refund a milestone plan covering part of the deposit after its deadline', async () => {
+    const planSeed = await nextOfferSeed(alice.publicKey);
+    const [planEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), alice.publicKey.toBuffer(), planSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const [plan] = PublicKey.findProgramAddressSync([Buffer.from("milestones"), planEscrow.toBuffer()], program.programId)
+    const planVault = getAssociatedTokenAddressSync(mintA.publicKey, planEscrow, true, tokenProgram);
+    const planAccounts = { maker: alice.publicKey, payee: bob.publicKey, mintA: mintA.publicKey, mintB: mintA.publicKey, makerMintAAta: aliceMintAPublicKey, payeeMintAAta: bobMintAPublicKey, escrow: planEscrow, vault: planVault, milestones: plan, tokenProgram };
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.makeArbitrated(depositAmount, provider.publicKey, bob.publicKey).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.createMilestones(new BN(Math.floor(Date.now() / 1000) + 2)).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.addMilestone("design", depositAmount.muln(4).divn(10)).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.approveMilestone(0).accountsPartial(planAccounts).signers([alice]).rpc()
+    )
+
+    // Every milestone is approved, but they only paid out part of the deposit
+    try {
+      await program.methods.refundMilestones().accountsPartial({ ...planAccounts, caller: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("a partly covered plan cannot be refunded before the deadline")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "MilestonesNotDue")
+    }
+
+    const before = new BN((await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount);
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    const signature = await program.methods.refundMilestones().accountsPartial({ ...planAccounts, caller: bob.publicKey }).signers([bob]).rpc();
+    await confirmTransaction(provider.connection, signature)
+    const after = new BN((await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount);
+    assert.strictEqual(after.sub(before).toString(), depositAmount.muln(6).divn(10).toString())
+    assert.isNull(await provider.connection.getAccountInfo(planEscrow))
+
+    const [settled] = (await eventsOf(signature)).filter((event) => event.name === "offerSettled");
+    assert.strictEqual(settled.data.payeeAmount.toString(), "0")
+    assert.strictEqual(settled.data.makerAmount.toString(), depositAmount.muln(6).divn(10).toString())
+  })
+
+  it('should vest a grant linearly and let the revoke authority claw back the rest', async () => {
+    const vestingSeed = new BN(0);
+    const [vesting] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), alice.publicKey.toBuffer(), vestingSeed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )
+    const vestingVault = getAssociatedTokenAddressSync(mintA.publicKey, vesting, true, tokenProgram);
+    const vestingAccounts = { creator: alice.publicKey, beneficiary: bob.publicKey, mintA: mintA.publicKey, creatorMintAAta: aliceMintAPublicKey, beneficiaryMintAAta: bobMintAPublicKey, vesting, vault: vestingVault, tokenProgram };
+    const now = Math.floor(Date.now() / 1000);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.createVesting(vestingSeed, depositAmount, new BN(now - 100), new BN(now - 100), new BN(now + 10_000), provider.publicKey)
+        .accountsPartial(vestingAccounts)
+        .signers([alice])
+        .rpc()
+    )
+    const bobBefore = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    const aliceBefore = new BN((await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount);
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.claimVested().accountsPartial(vestingAccounts).signers([bob]).rpc()
+    )
+    const claimed = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount).sub(bobBefore);
+    assert.isTrue(claimed.gtn(0) && claimed.lt(depositAmount.divn(10)))
+
+    try {
+      await program.methods.revoke().accountsPartial({ ...vestingAccounts, authority: bob.publicKey }).signers([bob]).rpc()
+      assert.fail("only the revoke authority may revoke")
+    } catch (err) {
+      assert.strictEqual(err.error.errorCode.code, "RevokeNotAllowed")
+    }
+
+    await confirmTransaction(
+      provider.connection,
+      await program.methods.revoke().accountsPartial({ ...vestingAccounts, authority: provider.publicKey }).rpc()
+    )
+    const bobAfter = new BN((await provider.connection.getTokenAccountBalance(bobMintAPublicKey)).value.amount);
+    const aliceAfter = new BN((await provider.connection.getTokenAccountBalance(aliceMintAPublicKey)).value.amount);
+    assert.strictEqual(bobAfter.sub(bobBefore).add(aliceAfter.sub(aliceBefore)).toString(), depositAmount.toString())
+    assert.isNull(await provider.connection.getAccountInfo(vesting))
+  })
+
+  it('should count alice\'s offers on her maker profile', async () => {
+    const [profile] = PublicKey.findProgramAddressSync([Buffer.from("maker"), alice.publicKey.toBuffer()], program.programId);
+    const account = await program.account.makerProfile.fetch(profile);
+
+    assert.strictEqual(account.offerCount.toNumber(), 20)
+    assert.strictEqual(account.filledCount.toNumber(), 14)
+    assert.strictEqual(account.refundedCount.toNumber(), 6)
   })
 });
 